@@ -4,6 +4,11 @@ set -euxo pipefail
 
 main() {
     cargo test
+    cargo test -p smlang-codegen --features graphviz,mermaid,plantuml,scxml,locations
+    # The examples assert their behaviour in main
+    for example in examples/*.rs; do
+        cargo run --example "$(basename "$example" .rs)" --features graphviz,mermaid,plantuml,scxml
+    done
     cargo check
 }
 
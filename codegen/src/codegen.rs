@@ -0,0 +1,916 @@
+// Move guards to return a Result
+
+use crate::parser::*;
+use proc_macro2::{Ident, Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use std::vec::Vec;
+use syn::{Fields, Lifetime, Stmt};
+
+/// Converts a `CamelCase` name to `snake_case`.
+pub fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c.is_uppercase() && prev_lower {
+            out.push('_');
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        out.extend(c.to_lowercase());
+    }
+    out
+}
+
+/// The identifier of a generated item, prefixed with the name of the state machine if it has one.
+fn generated_ident(sm: &ParsedStateMachine, item: &str) -> Ident {
+    match &sm.name {
+        Some(name) => format_ident!("{}{}", name.to_string(), item),
+        None => format_ident!("{}", item),
+    }
+}
+
+/// The identifier of a generated constant, prefixed with the name of the state machine if it has
+/// one.
+pub fn generated_const(sm: &ParsedStateMachine, item: &str) -> Ident {
+    match &sm.name {
+        Some(name) => format_ident!("{}_{}", snake_case(&name.to_string()).to_uppercase(), item),
+        None => format_ident!("{}", item),
+    }
+}
+
+/// Collects the lifetimes used in `tokens` into `found`, in order of first use, except for
+/// `'static`.
+fn lifetimes(tokens: TokenStream, found: &mut Vec<Lifetime>) {
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Group(group) => lifetimes(group.stream(), found),
+            TokenTree::Punct(punct) if punct.as_char() == '\'' => {
+                if let Some(TokenTree::Ident(ident)) = tokens.peek() {
+                    let lifetime = Lifetime {
+                        apostrophe: punct.span(),
+                        ident: ident.clone(),
+                    };
+                    if lifetime.ident != "static" && !found.contains(&lifetime) {
+                        found.push(lifetime);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Formats Rust code for a Markdown table cell.
+fn code_cell(code: String) -> String {
+    format!("`{}`", code.replace('|', "\\|"))
+}
+
+/// A Markdown table of the transitions of a region, for the documentation of the state machine.
+fn transition_table(region: &ParsedRegion) -> String {
+    let mut lines = Vec::new();
+
+    if let Some(name) = &region.name {
+        lines.push(format!("## `{}`", name));
+        lines.push(String::new());
+    }
+
+    lines.push(format!("Starting state: `{}`", region.start));
+    lines.push(String::new());
+
+    lines.push("| State | Event | Guard | Action | Next state |".to_string());
+    lines.push("|-------|-------|-------|--------|------------|".to_string());
+    for transition in region.transitions.iter() {
+        let state = match &transition.in_state {
+            Some(in_state) => code_cell(in_state.ident.to_string()),
+            None => "any".to_string(),
+        };
+
+        let guard = transition.guard_text().map_or(String::new(), code_cell);
+        let action = transition.action_text().map_or(String::new(), code_cell);
+        let next = transition
+            .out_state_text()
+            .map_or("*internal*".to_string(), code_cell);
+
+        lines.push(format!(
+            "| {} | {} | {} | {} | {} |",
+            state,
+            code_cell(transition.event_text()),
+            guard,
+            action,
+            next
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// The generated code of a region.
+struct RegionCode {
+    /// The states enum and its implementations.
+    types: TokenStream,
+    /// The arms of the match on the current state, evaluating to `Ok(Handled)` if there was
+    /// a transition.
+    transitions: Vec<TokenStream>,
+    /// The state machine fields remembering sub-states for history, with their types.
+    history_fields: Vec<(Ident, Ident)>,
+}
+
+/// A pattern matching a state regardless of its data.
+fn state_pattern(states_type: &Ident, state: &syn::Variant) -> TokenStream {
+    let sident = &state.ident;
+    match state.fields {
+        Fields::Unit => quote! { #states_type::#sident },
+        Fields::Unnamed(_) => quote! { #states_type::#sident(..) },
+        Fields::Named(_) => quote! { #states_type::#sident { .. } },
+    }
+}
+
+/// The state entered when transitioning to `state`, without data or with default data.
+fn initial_state(region: &ParsedRegion, states_type: &Ident, state: &str) -> TokenStream {
+    let leaf = &region.states[&region.initial_leaf(state)];
+    let ident = &leaf.ident;
+    match leaf.fields {
+        Fields::Unit => quote! { #states_type::#ident },
+        _ => quote! { #states_type::#ident(Default::default()) },
+    }
+}
+
+/// The names used for the history of a region.
+struct HistoryNames<'a> {
+    states_type: &'a Ident,
+    composites_type: &'a Ident,
+    history_type: Ident,
+    prefix: String,
+}
+
+impl HistoryNames<'_> {
+    /// The state machine field remembering the last active sub-state of `composite`.
+    fn field(&self, composite: &str) -> Ident {
+        format_ident!("{}history_{}", self.prefix, snake_case(composite))
+    }
+}
+
+/// An expression resuming `composite` from its remembered sub-state, or entering its initial
+/// sub-state if there is none.
+fn resume_history(
+    region: &ParsedRegion,
+    names: &HistoryNames,
+    composite: &str,
+    history: History,
+) -> TokenStream {
+    let field = names.field(composite);
+    let history_type = &names.history_type;
+    let composites_type = names.composites_type;
+    let initial = initial_state(region, names.states_type, composite);
+
+    let children = region
+        .initial_states
+        .keys()
+        .filter(|c| region.parents.get(*c).map(|p| p.as_str()) == Some(composite));
+
+    let arms = children.into_iter().map(|child| {
+        let ident = &region.state_definitions[child].ident;
+        let resume = if history == History::Deep {
+            resume_history(region, names, child, history)
+        } else {
+            initial_state(region, names.states_type, child)
+        };
+        quote! {
+            Some(#history_type::Composite(#composites_type::#ident)) => #resume,
+        }
+    });
+
+    quote! {
+        match self.#field.take() {
+            Some(#history_type::State(state)) => state,
+            #(#arms)*
+            _ => #initial,
+        }
+    }
+}
+
+/// Runs the actions of a transition followed by `then`, which is skipped if an action fails
+/// with an `action_error`.
+fn run_actions(sm: &ParsedStateMachine, actions: Option<Stmt>, then: TokenStream) -> TokenStream {
+    let error_type = generated_ident(sm, "Error");
+    match (&sm.action_error, actions) {
+        (Some(action_error), Some(actions)) => {
+            let value = match actions {
+                Stmt::Semi(expr, _) => quote! { #expr },
+                actions => quote! { { #actions } },
+            };
+            quote! {
+                match ::smlang::ActionResult::<#action_error>::into_action_result(#value) {
+                    Ok(()) => { #then }
+                    Err(error) => Err(#error_type::ActionError(error)),
+                }
+            }
+        }
+        (_, actions) => quote! {
+            #actions;
+            #then
+        },
+    }
+}
+
+/// Generates the code of a region, where `current_state` is the place holding its state.
+fn generate_region(
+    sm: &ParsedStateMachine,
+    region: &ParsedRegion,
+    states_type: &Ident,
+    composites_type: &Ident,
+    current_state: &TokenStream,
+) -> RegionCode {
+    let state_list: Vec<_> = region.states.values().collect();
+    let events_type = generated_ident(sm, "Events");
+    let error_type = generated_ident(sm, "Error");
+    let state_machine_type = generated_ident(sm, "StateMachine");
+
+    let i = region.starting_state.ident.clone();
+    let starting_state = match region.starting_state.fields {
+        Fields::Unit => quote! { #i },
+        _ => quote! { #i(Default::default()) },
+    };
+
+    let history_names = HistoryNames {
+        states_type,
+        composites_type,
+        history_type: format_ident!("{}History", states_type),
+        prefix: region.name.as_ref().map_or(String::new(), |name| {
+            format!("{}_", snake_case(&name.to_string()))
+        }),
+    };
+
+    let (states_doc, composites_doc) = match &region.name {
+        Some(name) => (
+            format!(
+                "List of auto-generated states of the `{}` region.\n\nThe transitions between them are listed in the documentation of [`{}`].",
+                name, state_machine_type
+            ),
+            format!(
+                "List of auto-generated states of the `{}` region which have sub-states.",
+                name
+            ),
+        ),
+        None => (
+            format!(
+                "List of auto-generated states.\n\nThe transitions between them are listed in the documentation of [`{}`].",
+                state_machine_type
+            ),
+            "List of auto-generated states which have sub-states.".to_string(),
+        ),
+    };
+
+    let transitions: Vec<proc_macro2::TokenStream> = region
+        .states_events_mapping
+        .iter()
+        .map(|(state, trans)| {
+            //get the state ident
+            let state = region
+                .states
+                .get(state)
+                .expect("should be able to get state");
+            //see if we should capture state data
+            let sdata: Option<proc_macro2::TokenStream> = match state.fields {
+                Fields::Unit => None,
+                _ => Some(quote! { (ref state) }),
+            };
+            let sident = state.ident.clone();
+            let sname = sident.to_string();
+
+            //create the event matches
+            let events: Vec<proc_macro2::TokenStream> = trans
+                .iter()
+                .map(|t| {
+                    let t = t.clone();
+                    let eident = t.event.clone();
+                    let pat = t.event_pattern.map(|p| {
+                        quote! {
+                            (ref mut event @ #p)
+                        }
+                    });
+                    let guard = t.guard.map(|a| match &sm.guard_error {
+                        //the first error of a failed guard is kept in case no other transition
+                        //is taken
+                        Some(guard_error) => quote! {
+                            if match ::smlang::GuardResult::<#guard_error>::into_guard_result(#a) {
+                                Ok(()) => true,
+                                Err(error) => {
+                                    guard_error = guard_error.take().or(error);
+                                    false
+                                }
+                            }
+                        },
+                        None => quote! {
+                            if #a
+                        },
+                    });
+
+                    let actions = t.actions;
+
+                    let transition = if let Some(out_state) = t.out_state {
+                        let oname = out_state.to_string();
+
+                        //composite states are entered through their initial sub-states, or
+                        //resumed from their history
+                        let next = if let Some(history) = t.out_state_history {
+                            resume_history(region, &history_names, &oname, history)
+                        } else if region.is_composite(&oname) {
+                            initial_state(region, states_type, &oname)
+                        } else {
+                            let out_state_data_expr = t.out_state_data_expr.map(|expr| {
+                                quote! {
+                                    (#expr)
+                                }
+                            });
+                            quote! { #states_type::#out_state #out_state_data_expr }
+                        };
+
+                        //exit the source states before the actions, enter the destinations after
+                        let source = t
+                            .in_state
+                            .as_ref()
+                            .map_or(sname.clone(), |s| s.ident.to_string());
+                        let common = region.transition_domain(&source, &oname);
+                        let mut exited = region.state_path(&sname).split_off(common);
+                        exited.reverse();
+                        let entered = if t.out_state_history.is_some() {
+                            region.state_path(&oname).split_off(common)
+                        } else {
+                            region
+                                .state_path(&region.initial_leaf(&oname))
+                                .split_off(common)
+                        };
+
+                        let exit = exited
+                            .iter()
+                            .filter_map(|s| region.exit_action(s))
+                            .map(|e| quote! { #e; });
+                        let entry = entered
+                            .iter()
+                            .filter_map(|s| region.entry_action(s))
+                            .map(|e| quote! { #e; });
+
+                        //exited composite states with history remember their active sub-state
+                        let mut keep_previous = false;
+                        let history_type = &history_names.history_type;
+                        let record: Vec<_> = exited
+                            .iter()
+                            .enumerate()
+                            .skip(1)
+                            .filter(|(_, s)| region.history_states.contains(*s))
+                            .map(|(i, s)| {
+                                let field = history_names.field(s);
+                                if i == 1 {
+                                    keep_previous = true;
+                                    quote! { self.#field = Some(#history_type::State(previous)); }
+                                } else {
+                                    let child = &region.state_definitions[&exited[i - 1]].ident;
+                                    quote! {
+                                        self.#field =
+                                            Some(#history_type::Composite(#composites_type::#child));
+                                    }
+                                }
+                            })
+                            .collect();
+                        //the history is recorded before resuming it, as the destination may be
+                        //the composite state being left. Other destinations may use the state
+                        //data, so the current state is only replaced once they are evaluated.
+                        let assign = match (keep_previous, t.out_state_history.is_some()) {
+                            (true, true) => quote! {
+                                let previous = core::mem::take(&mut #current_state);
+                                #(#record)*
+                                #current_state = #next;
+                            },
+                            (true, false) => quote! {
+                                let next = #next;
+                                let previous = core::mem::replace(&mut #current_state, next);
+                                #(#record)*
+                            },
+                            (false, _) => quote! {
+                                #(#record)*
+                                #current_state = #next;
+                            },
+                        };
+
+                        //when resuming, the entered sub-states are only known at runtime
+                        let resumed_entry = if t.out_state_history.is_some() {
+                            let depth = region.state_path(&oname).len();
+                            let arms: Vec<_> = state_list
+                                .iter()
+                                .filter_map(|leaf| {
+                                    let path = region.state_path(&leaf.ident.to_string());
+                                    if !path.contains(&oname) {
+                                        return None;
+                                    }
+                                    let entry: Vec<_> = path[depth..]
+                                        .iter()
+                                        .filter_map(|s| region.entry_action(s))
+                                        .collect();
+                                    if entry.is_empty() {
+                                        return None;
+                                    }
+                                    let pattern = state_pattern(states_type, leaf);
+                                    Some(quote! { #pattern => { #(#entry;)* } })
+                                })
+                                .collect();
+                            if arms.is_empty() {
+                                None
+                            } else {
+                                Some(quote! {
+                                    match #current_state {
+                                        #(#arms)*
+                                        _ => {}
+                                    }
+                                })
+                            }
+                        } else {
+                            None
+                        };
+
+                        let enter = quote! {
+                            #assign
+                            #(#entry)*
+                            #resumed_entry
+                            Ok(::smlang::Handled::Transition(&#current_state))
+                        };
+
+                        //fallible actions run before the exit actions, so that an aborted
+                        //transition leaves nothing behind
+                        if sm.action_error.is_some() {
+                            run_actions(sm, actions, quote! { #(#exit)* #enter })
+                        } else {
+                            let transition = run_actions(sm, actions, enter);
+                            quote! {
+                                #(#exit)*
+                                #transition
+                            }
+                        }
+                    } else {
+                        run_actions(
+                            sm,
+                            actions,
+                            quote! { Ok(::smlang::Handled::Internal(&#current_state)) },
+                        )
+                    };
+
+                    quote! {
+                        #events_type:: #eident #pat #guard => {
+                            #transition
+                        }
+                    }
+                })
+                .collect();
+
+            //events which only failed the guards of their transitions are told apart from events
+            //without transitions
+            let has_guards = trans.iter().any(|t| t.guard.is_some());
+            let guard_failed_error = if sm.guard_error.is_some() {
+                quote! { guard_error.map_or(#error_type::GuardFailed, #error_type::GuardError) }
+            } else {
+                quote! { #error_type::GuardFailed }
+            };
+            let guard_failed = trans.iter().filter(|t| t.guard.is_some()).map(|t| {
+                let eident = &t.event;
+                let pat = t.event_pattern.as_ref().map(|p| quote! { (#p) });
+                quote! {
+                    #[allow(unreachable_patterns, unused_variables)]
+                    #events_type:: #eident #pat => Err(#guard_failed_error),
+                }
+            });
+            let guard_error = if has_guards && sm.guard_error.is_some() {
+                Some(quote! { let mut guard_error = None; })
+            } else {
+                None
+            };
+
+            quote! {
+                #states_type:: #sident #sdata => {
+                    #guard_error
+                    match &mut e {
+                        #(#events,)*
+                        #(#guard_failed)*
+                        #[allow(unreachable_patterns)]
+                        _ => Err(#error_type::InvalidEvent),
+                    }
+                }
+            }
+        })
+        .collect();
+
+    // Composite states are not states of their own, but can be queried on the states
+    let composite_list: Vec<_> = region
+        .initial_states
+        .keys()
+        .map(|s| &region.state_definitions[s].ident)
+        .collect();
+
+    let composites = if composite_list.is_empty() {
+        None
+    } else {
+        let is_in: Vec<proc_macro2::TokenStream> = composite_list
+            .iter()
+            .map(|composite| {
+                let composite_name = composite.to_string();
+                let sub_states = state_list
+                    .iter()
+                    .filter(|s| {
+                        region
+                            .state_path(&s.ident.to_string())
+                            .contains(&composite_name)
+                    })
+                    .map(|s| state_pattern(states_type, s));
+                quote! {
+                    #composites_type::#composite => matches!(self, #(#sub_states)|*)
+                }
+            })
+            .collect();
+
+        Some(quote! {
+            #[doc = #composites_doc]
+            #[allow(missing_docs)]
+            #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+            pub enum #composites_type { #(#composite_list),* }
+
+            impl #states_type {
+                /// Returns `true` if the state is one of the sub-states of `composite`, at any depth.
+                pub fn is_in(&self, composite: #composites_type) -> bool {
+                    match composite {
+                        #(#is_in),*
+                    }
+                }
+            }
+        })
+    };
+
+    // States without transitions are final, the state machine stays in them
+    let terminal: Vec<_> = state_list
+        .iter()
+        .filter(|s| {
+            !region
+                .states_events_mapping
+                .contains_key(&s.ident.to_string())
+        })
+        .map(|s| state_pattern(states_type, s))
+        .collect();
+    let is_terminal = if terminal.is_empty() {
+        quote! { false }
+    } else {
+        quote! { matches!(self, #(#terminal)|*) }
+    };
+
+    // Composite states with history remember their last active sub-state in the state machine
+    let history_type = &history_names.history_type;
+    let history_fields: Vec<_> = region
+        .history_states
+        .iter()
+        .map(|s| (history_names.field(s), history_type.clone()))
+        .collect();
+    let history = if history_fields.is_empty() {
+        None
+    } else {
+        Some(quote! {
+            /// A remembered sub-state of a composite state, resumed by history transitions.
+            #[allow(dead_code)]
+            enum #history_type {
+                State(#states_type),
+                Composite(#composites_type),
+            }
+        })
+    };
+
+    let states_attrs = &sm.states_attrs;
+
+    RegionCode {
+        types: quote! {
+            #[doc = #states_doc]
+            #[allow(missing_docs)]
+            #[derive(PartialEq)]
+            #(#states_attrs)*
+            pub enum #states_type { #(#state_list),* }
+
+            #composites
+
+            #history
+
+            impl #states_type {
+                /// Returns `true` if no event is handled in the state, so it is never left.
+                pub fn is_terminal(&self) -> bool {
+                    #is_terminal
+                }
+            }
+
+            impl Default for #states_type {
+                fn default() -> Self {
+                    Self::#starting_state
+                }
+            }
+        },
+        transitions,
+        history_fields,
+    }
+}
+
+pub fn generate_code(sm: &ParsedStateMachine) -> TokenStream {
+    let states_attrs = &sm.states_attrs;
+    let states_type = generated_ident(sm, "States");
+    let events_type = generated_ident(sm, "Events");
+    let error_type = generated_ident(sm, "Error");
+    let state_machine_type = generated_ident(sm, "StateMachine");
+    let region_transitions_type = generated_ident(sm, "RegionTransitions");
+    let context_type = match &sm.context_type {
+        Some(context_type) => quote! { #context_type },
+        None => {
+            let context_type = generated_ident(sm, "Context");
+            quote! { #context_type }
+        }
+    };
+
+    // A state machine without regions generates its states directly, one with regions has a
+    // set of states for each region
+    let (states, process, result_type, transitions, history_fields) = if sm.has_regions() {
+        let mut types = Vec::new();
+        let mut history_fields = Vec::new();
+        let mut fields = Vec::new();
+        let mut field_types = Vec::new();
+        let mut processes = Vec::new();
+        let mut transitions = Vec::new();
+        for region in sm.regions.iter() {
+            let name = region.name.as_ref().unwrap();
+            let region_states_type = generated_ident(sm, &format!("{}States", name));
+            let composites_type = generated_ident(sm, &format!("{}CompositeStates", name));
+            let field = format_ident!("{}", snake_case(&name.to_string()));
+
+            let code = generate_region(
+                sm,
+                region,
+                &region_states_type,
+                &composites_type,
+                &quote! { self.state.#field },
+            );
+            let region_transitions = &code.transitions;
+            processes.push(quote! {
+                let #field = match self.state.#field {
+                    #(#region_transitions)*
+                    _ => Err(#error_type::InvalidEvent),
+                };
+            });
+            types.push(code.types);
+            history_fields.extend(code.history_fields);
+            fields.push(field);
+            field_types.push(region_states_type);
+            transitions.extend(code.transitions);
+        }
+
+        let states = quote! {
+            #(#types)*
+
+            /// The auto-generated states of all regions.
+            #[derive(PartialEq, Default)]
+            #(#states_attrs)*
+            pub struct #states_type {
+                #(
+                    #[allow(missing_docs)]
+                    pub #fields: #field_types
+                ),*
+            }
+
+            /// The outcome of processing an event in each region, `Ok(Handled)` for the
+            /// regions which handled it, and the `Error` telling why not for the others.
+            pub struct #region_transitions_type<'a> {
+                #(
+                    #[allow(missing_docs)]
+                    pub #fields: Result<::smlang::Handled<&'a #field_types>, #error_type>
+                ),*
+            }
+        };
+        let process = quote! {
+            #(#processes)*
+            #region_transitions_type { #(#fields),* }
+        };
+
+        (
+            states,
+            process,
+            quote! { #region_transitions_type },
+            transitions,
+            history_fields,
+        )
+    } else {
+        let code = generate_region(
+            sm,
+            &sm.regions[0],
+            &states_type,
+            &generated_ident(sm, "CompositeStates"),
+            &quote! { self.state },
+        );
+        let region_transitions = &code.transitions;
+        let process = quote! {
+            match self.state {
+                #(#region_transitions)*
+                _ => Err(#error_type::InvalidEvent),
+            }
+        };
+
+        (
+            code.types,
+            process,
+            quote! { Result<::smlang::Handled<&#states_type>, #error_type> },
+            code.transitions,
+            code.history_fields,
+        )
+    };
+
+    let process_async: Option<Ident> = if transitions
+        .iter()
+        .find(|t| t.to_string().contains(".await"))
+        .is_some()
+    {
+        Some(Ident::new("async", Span::call_site()))
+    } else {
+        None
+    };
+
+    //hack in async, look for `.await`
+
+    let (history_names, history_types): (Vec<_>, Vec<_>) = history_fields.into_iter().unzip();
+
+    let guard_error = sm.guard_error.as_ref().map(|guard_error| {
+        quote! {
+            /// A guard failed with an error, and no other transition was taken.
+            GuardError(#guard_error),
+        }
+    });
+    // The events enum is generated when the events are declared, with the lifetimes of their data
+    let events = sm.events.as_ref().map(|events| {
+        let mut event_lifetimes = Vec::new();
+        for event in events.iter() {
+            lifetimes(event.fields.to_token_stream(), &mut event_lifetimes);
+        }
+        let event_lifetimes = if event_lifetimes.is_empty() {
+            None
+        } else {
+            Some(quote! { <#(#event_lifetimes),*> })
+        };
+        let events_attrs = &sm.events_attrs;
+
+        quote! {
+            /// List of auto-generated events.
+            #[allow(missing_docs)]
+            #(#events_attrs)*
+            pub enum #events_type #event_lifetimes { #(#events),* }
+        }
+    });
+
+    let temporary_context = sm
+        .temporary_context
+        .as_ref()
+        .map(|ty| quote! { temporary_context: #ty, });
+    let action_error = sm.action_error.as_ref().map(|action_error| {
+        quote! {
+            /// An action failed, so the transition was aborted and the state did not change.
+            ActionError(#action_error),
+        }
+    });
+
+    // The transitions are listed in the documentation of the state machine
+    let tables: Vec<String> = sm.regions.iter().map(transition_table).collect();
+    let state_machine_doc = format!(
+        "State machine structure definition.\n\n# Transitions\n\n{}",
+        tables.join("\n\n")
+    );
+
+    // Proc macros cannot emit warnings, so a deprecated constant is used on the span of each
+    // unused event instead
+    let warnings = sm.unused_events.iter().map(|event| {
+        let note = format!(
+            "Event {} is never handled by a transition. Allow unused events with `#![allow(unused_events)]`.",
+            event
+        );
+        quote_spanned! {event.span()=>
+            const _: () = {
+                #[deprecated(note = #note)]
+                #[allow(non_upper_case_globals)]
+                const unused_event: () = ();
+                unused_event
+            };
+        }
+    });
+
+    // Build the states and events output
+    quote! {
+
+        #states
+
+        #events
+
+        #(#warnings)*
+
+        /// List of possible errors when processing an event.
+        #[derive(Debug, PartialEq)]
+        pub enum #error_type {
+            /// No transition is defined for the event in the current state.
+            InvalidEvent,
+            /// The event has transitions in the current state, but all of their guards failed.
+            GuardFailed,
+            #guard_error
+            #action_error
+        }
+
+        #[doc = #state_machine_doc]
+        pub struct #state_machine_type {
+            state: #states_type,
+            #(#history_names: Option<#history_types>,)*
+            context: #context_type
+        }
+
+        impl #state_machine_type {
+            /// Creates a new state machine with the specified starting state.
+            #[inline(always)]
+            pub fn new(context: #context_type) -> Self {
+                Self::new_with_state(context, Default::default())
+            }
+
+            /// Creates a new state machine with an initial state.
+            #[inline(always)]
+            pub fn new_with_state(context: #context_type, initial_state: #states_type) -> Self {
+                Self {
+                    state: initial_state,
+                    #(#history_names: None,)*
+                    context
+                }
+            }
+
+            /// Returns the current state.
+            #[inline(always)]
+            pub fn state(&self) -> &#states_type {
+                &self.state
+            }
+
+            /// Returns the current context.
+            #[inline(always)]
+            pub fn context(&self) -> &#context_type {
+                &self.context
+            }
+
+            /// Returns the current context as a mutable reference.
+            #[inline(always)]
+            pub fn context_mut(&mut self) -> &mut #context_type {
+                &mut self.context
+            }
+
+            /// Process an event.
+            ///
+            /// It will return `Ok(Handled::Transition(&NextState))` if the transition was
+            /// successful, `Ok(Handled::Internal(&State))` if a transition without a destination
+            /// state handled the event, or an `Error` telling why the event was not handled. With
+            /// regions, this is returned for each region.
+            #[allow(unused)]
+            pub #process_async fn process_event(
+                &mut self,
+                #temporary_context
+                mut e: #events_type,
+            ) -> #result_type {
+                let mut ctx = &mut self.context;
+                #process
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::StateMachine;
+
+    #[test]
+    fn transitions_table() {
+        let sm = syn::parse2::<StateMachine>(quote! {
+            states: {
+                Working { states: { *Heating, Cooling } },
+            },
+            transitions: {
+                *Idle + Start(Speed { rpm, .. }) [rpm > 0 || ctx.forced()] / ctx.start(rpm); = Working,
+                Heating + Cool = Cooling(1),
+                Cooling(u32) + Tick / { ctx.tick(); ctx.log() },
+                Idle + Resume = Working(H),
+                _ + Stop = Idle,
+            }
+        })
+        .unwrap();
+        let sm = ParsedStateMachine::new(sm).unwrap();
+
+        assert_eq!(
+            transition_table(&sm.regions[0]),
+            "Starting state: `Idle`
+
+| State | Event | Guard | Action | Next state |
+|-------|-------|-------|--------|------------|
+| `Idle` | `Start(Speed { rpm , .. })` | `rpm > 0 \\|\\| ctx . forced ()` | `ctx . start (rpm)` | `Working` |
+| `Heating` | `Cool` |  |  | `Cooling(1)` |
+| `Cooling` | `Tick` |  | `{ ctx . tick () ; ctx . log () }` | *internal* |
+| `Idle` | `Resume` |  |  | `Working(H)` |
+| any | `Stop` |  |  | `Idle` |"
+        );
+    }
+}
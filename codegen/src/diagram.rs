@@ -0,0 +1,329 @@
+#[cfg(feature = "diagrams")]
+use crate::parser::ParsedRegion;
+use crate::parser::ParsedStateMachine;
+use std::path::{Path, PathBuf};
+
+/// The diagram formats, with the feature enabling each of them and their file extensions.
+const FORMATS: &[(&str, &[&str])] = &[
+    ("graphviz", &["dot", "gv", "svg"]),
+    ("mermaid", &["mmd", "mermaid"]),
+    ("plantuml", &["puml", "plantuml"]),
+    ("scxml", &["scxml"]),
+];
+
+/// A diagram of the state machine in one of the enabled formats.
+pub struct Diagram {
+    /// The name of the constant holding the diagram in the generated code.
+    pub constant: &'static str,
+    /// The documentation of the constant.
+    pub doc: &'static str,
+    /// The file extensions the diagram is written to.
+    pub extensions: &'static [&'static str],
+    pub text: String,
+}
+
+/// Generates the diagrams in all the enabled formats.
+#[cfg_attr(not(feature = "diagrams"), allow(unused_variables))]
+pub fn generate(sm: &ParsedStateMachine) -> Vec<Diagram> {
+    vec![
+        #[cfg(feature = "graphviz")]
+        Diagram {
+            constant: "STATEMACHINE_DOT",
+            doc: "The diagram of the state machine in graphviz's `dot` syntax.",
+            extensions: &["dot", "gv"],
+            text: crate::diagramgen::generate_diagram(sm),
+        },
+        #[cfg(feature = "mermaid")]
+        Diagram {
+            constant: "STATEMACHINE_MERMAID",
+            doc: "The diagram of the state machine in Mermaid's `stateDiagram-v2` syntax.",
+            extensions: &["mmd", "mermaid"],
+            text: crate::mermaidgen::generate_diagram(sm),
+        },
+        #[cfg(feature = "plantuml")]
+        Diagram {
+            constant: "STATEMACHINE_PLANTUML",
+            doc: "The diagram of the state machine in PlantUML's state diagram syntax.",
+            extensions: &["puml", "plantuml"],
+            text: crate::plantumlgen::generate_diagram(sm),
+        },
+        #[cfg(feature = "scxml")]
+        Diagram {
+            constant: "STATEMACHINE_SCXML",
+            doc: "The state machine as a W3C SCXML document.",
+            extensions: &["scxml"],
+            text: crate::scxmlgen::generate_diagram(sm),
+        },
+    ]
+}
+
+/// All the states of a region, sub-states and the states containing them, in order of first use
+/// in the transitions, followed by the ones only used in the state definitions.
+#[cfg(feature = "diagrams")]
+pub fn states_in_order(region: &ParsedRegion) -> Vec<String> {
+    let mut states: Vec<String> = Vec::new();
+    for transition in region.transitions.iter() {
+        let used = transition
+            .in_state
+            .iter()
+            .map(|s| &s.ident)
+            .chain(transition.out_state.iter());
+        for state in used {
+            let mut path = region.state_path(&state.to_string());
+            path.retain(|s| !states.contains(s));
+            states.extend(path);
+        }
+    }
+
+    let remaining: Vec<String> = region
+        .states
+        .keys()
+        .chain(region.initial_states.keys())
+        .filter(|s| !states.contains(s))
+        .cloned()
+        .collect();
+    states.extend(remaining);
+
+    states
+}
+
+/// The diagram of a region, with the names of its states prefixed with `prefix` to tell them apart
+/// from the states of other regions.
+#[cfg(feature = "diagrams")]
+pub struct RegionDiagram<'a> {
+    pub region: &'a ParsedRegion,
+    pub prefix: String,
+    /// All states, leaves and composites, in order of first use.
+    pub states: Vec<String>,
+}
+
+#[cfg(feature = "diagrams")]
+impl<'a> RegionDiagram<'a> {
+    pub fn new(region: &'a ParsedRegion, prefix: String) -> Self {
+        RegionDiagram {
+            region,
+            prefix,
+            states: states_in_order(region),
+        }
+    }
+
+    /// The name of a state, with the prefix of the region.
+    pub fn name(&self, state: &str) -> String {
+        format!("{}{}", self.prefix, state)
+    }
+}
+
+/// The label of a transition for formats drawing its states separately: its event, guard and
+/// action.
+#[cfg(any(feature = "mermaid", feature = "plantuml"))]
+pub fn transition_label(transition: &crate::parser::StateTransition) -> String {
+    use crate::parser::History;
+
+    let mut label = transition.event_text();
+
+    if let Some(guard) = transition.guard_text() {
+        label += format!(" [{}]", guard).as_str();
+    };
+
+    if let Some(action) = transition.action_text() {
+        label += format!(" / {}", action).as_str();
+    };
+
+    match &transition.out_state_history {
+        Some(History::Shallow) => label += " (H)",
+        Some(History::Deep) => label += " (H*)",
+        None => {}
+    }
+
+    label
+}
+
+/// The composite state a transition is drawn in by formats nesting states inside of composites,
+/// the innermost one containing both of its states, or `None` for the top of the region.
+#[cfg(any(feature = "mermaid", feature = "plantuml"))]
+pub fn container(
+    region: &ParsedRegion,
+    transition: &crate::parser::StateTransition,
+) -> Option<String> {
+    let mut source = match &transition.in_state {
+        Some(in_state) => region.state_path(&in_state.ident.to_string()),
+        None => return None,
+    };
+    source.pop();
+
+    match &transition.out_state {
+        Some(out_state) => {
+            let mut target = region.state_path(&out_state.to_string());
+            target.pop();
+            source
+                .iter()
+                .zip(target.iter())
+                .take_while(|(s, t)| s == t)
+                .last()
+                .map(|(s, _)| s.clone())
+        }
+        None => source.pop(),
+    }
+}
+
+/// Writes the diagram of the format matching the extension of the path given with `diagram`,
+/// relative to the crate using the macro. Without a path, nothing is written.
+pub fn write(sm: &ParsedStateMachine, diagrams: &[Diagram]) -> syn::Result<()> {
+    let path = match &sm.diagram {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+    let span = path.span();
+
+    let mut full_path =
+        PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string()));
+    full_path.push(path.value());
+    let extension = full_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+
+    // The graphviz diagram always comes first, and is rendered for .svg paths
+    let diagram = diagrams.iter().find(|d| d.extensions.contains(&extension));
+    #[cfg(feature = "graphviz")]
+    let diagram = diagram.or(diagrams.first().filter(|_| extension == "svg"));
+    let diagram = match diagram {
+        Some(diagram) => diagram,
+        None => return Err(unsupported(span, extension)),
+    };
+
+    if let Some(parent) = full_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| {
+            syn::Error::new(
+                span,
+                format!("Failed to create {}: {}", parent.display(), e),
+            )
+        })?;
+    }
+
+    #[cfg(feature = "graphviz")]
+    if extension == "svg" {
+        return render(&full_path, &diagram.text).map_err(|e| syn::Error::new(span, e));
+    }
+
+    write_if_changed(&full_path, &diagram.text).map_err(|e| {
+        syn::Error::new(
+            span,
+            format!("Failed to write {}: {}", full_path.display(), e),
+        )
+    })
+}
+
+/// The error for a diagram path whose format is not enabled, or not supported at all.
+fn unsupported(span: proc_macro2::Span, extension: &str) -> syn::Error {
+    if let Some((feature, _)) = FORMATS.iter().find(|(_, e)| e.contains(&extension)) {
+        syn::Error::new(
+            span,
+            format!(
+                "Writing .{} diagrams requires the `{}` feature.",
+                extension, feature
+            ),
+        )
+    } else {
+        let extensions: Vec<String> = FORMATS
+            .iter()
+            .flat_map(|(_, e)| e.iter())
+            .map(|e| format!("\".{}\"", e))
+            .collect();
+        syn::Error::new(
+            span,
+            format!(
+                "Unknown diagram file type, supported extensions: [{}]",
+                extensions.join(", ")
+            ),
+        )
+    }
+}
+
+/// Writes `contents` to `path`, leaving it untouched if it already has them so that tools
+/// watching the file are not triggered on every build.
+fn write_if_changed(path: &Path, contents: &str) -> std::io::Result<()> {
+    if std::fs::read_to_string(path).is_ok_and(|current| current == contents) {
+        return Ok(());
+    }
+    std::fs::write(path, contents)
+}
+
+/// Renders the diagram to an svg file with graphviz's `dot`.
+#[cfg(feature = "graphviz")]
+fn render(path: &Path, diagram: &str) -> Result<(), String> {
+    use std::io::Write;
+
+    // Start the 'dot' process.
+    let mut process = std::process::Command::new("dot")
+        .arg("-Tsvg")
+        .arg("-o")
+        .arg(path)
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|_| {
+            "Failed to execute 'dot'. Are you sure graphviz is installed? Use a .dot path to write the diagram without rendering it.".to_string()
+        })?;
+
+    // Write the dot syntax string to the 'dot' process stdin.
+    if let Some(stdin) = process.stdin.as_mut() {
+        stdin
+            .write_all(diagram.as_bytes())
+            .map_err(|e| format!("Failed to write to 'dot': {}", e))?;
+    }
+
+    // Check the graphviz return status to see if it was successful.
+    match process.wait() {
+        Ok(status) if status.success() => Ok(()),
+        _ => Err("'dot' failed to render the diagram.".to_string()),
+    }
+}
+
+/// The state machines drawn by the tests of the diagram formats.
+#[cfg(all(test, feature = "diagrams"))]
+pub mod fixtures {
+    use crate::parser::{ParsedStateMachine, StateMachine};
+    use quote::quote;
+
+    /// Parses and validates a state machine.
+    pub fn parse(tokens: proc_macro2::TokenStream) -> ParsedStateMachine {
+        let sm = syn::parse2::<StateMachine>(tokens).unwrap();
+        ParsedStateMachine::new(sm).unwrap()
+    }
+
+    /// Transitions with event patterns, guards, actions and state data, and internal and
+    /// wildcard transitions.
+    pub fn transitions() -> ParsedStateMachine {
+        parse(quote! {
+            transitions: {
+                *State1 + Event1(MyEventData { x: 1, .. }) [guard] / action(); = State2(1),
+                State2(u32) + Event2 [*state > 0 && ready()] / { a(); b() } = State1,
+                State2(u32) + Event3 / count();,
+                _ + Reset = State1,
+            }
+        })
+    }
+
+    /// Two regions, one of them with a composite state which is resumed with history.
+    pub fn composite_states_and_regions() -> ParsedStateMachine {
+        parse(quote! {
+            regions: {
+                Main {
+                    states: {
+                        Operational { states: { *Idle, Busy } },
+                    },
+                    transitions: {
+                        *Operational + Fault = Error,
+                        Idle + Start = Busy,
+                        Error + Reset = Operational(H),
+                    },
+                },
+                Power {
+                    transitions: {
+                        *On + Off = Off,
+                    },
+                },
+            },
+        })
+    }
+}
@@ -0,0 +1,302 @@
+use crate::diagram::RegionDiagram;
+use crate::parser::*;
+use quote::quote;
+
+/// Escapes text for use inside of a quoted 'dot' string.
+fn escape(v: String) -> String {
+    v.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// The text of a transition as written in the DSL, without the starting state marker.
+fn transition_label(transition: &StateTransition) -> String {
+    let mut label = match &transition.in_state {
+        Some(in_state) => quote! { #in_state }.to_string(),
+        None => "_".to_string(),
+    };
+    label += format!(" + {}", transition.event_text()).as_str();
+
+    if let Some(guard) = transition.guard_text() {
+        label += format!(" [{}]", guard).as_str();
+    };
+
+    if let Some(actions) = &transition.actions {
+        label += format!(" / {}", quote! {#actions}).as_str();
+    };
+
+    if let Some(out_state) = transition.out_state_text() {
+        label += format!(" = {}", out_state).as_str();
+    };
+
+    escape(label)
+}
+
+fn node(diagram: &RegionDiagram, state: &str) -> String {
+    format!("\"{}\"", diagram.name(state))
+}
+
+fn cluster(diagram: &RegionDiagram, composite: &str) -> String {
+    format!("\"cluster_{}{}\"", diagram.prefix, composite)
+}
+
+/// The node an edge to or from `state` is attached to, with the attribute clipping it to the
+/// cluster of `state` if it is composite.
+fn endpoint(diagram: &RegionDiagram, state: &str, clip: &str) -> (String, Option<String>) {
+    if diagram.region.is_composite(state) {
+        (
+            node(diagram, &diagram.region.initial_leaf(state)),
+            Some(format!("{}={}", clip, cluster(diagram, state))),
+        )
+    } else {
+        (node(diagram, state), None)
+    }
+}
+
+/// Writes the states inside of `parent`, or at the top of the region for `None`.
+fn write_states(
+    diagram: &RegionDiagram,
+    out: &mut Vec<String>,
+    parent: Option<&String>,
+    indent: &str,
+) {
+    for state in diagram
+        .states
+        .iter()
+        .filter(|s| diagram.region.parents.get(*s) == parent)
+    {
+        if diagram.region.is_composite(state) {
+            out.push(format!("{}subgraph {} {{", indent, cluster(diagram, state)));
+            out.push(format!("{}    label=\"{}\";", indent, state));
+            write_states(diagram, out, Some(state), &format!("{}    ", indent));
+            out.push(format!("{}}}", indent));
+        } else {
+            let variant = &diagram.region.states[state];
+            out.push(format!(
+                "{}{} [shape=box color=\"red\" fillcolor=\"#ffbb33\" style=filled label=\"{}\"]",
+                indent,
+                node(diagram, state),
+                escape(quote! { #variant }.to_string())
+            ));
+        }
+    }
+}
+
+/// Generates a string containing 'dot' syntax to generate a statemachine diagram with graphviz.
+///
+/// Transitions are drawn as numbered edges, with the text of each number listed next to the
+/// diagram. Wildcard transitions are drawn once from a `_` node, and internal transitions as
+/// dashed self-loops.
+pub fn generate_diagram(sm: &ParsedStateMachine) -> String {
+    let mut lines = Vec::new();
+    let mut labels = Vec::new();
+
+    for region in sm.regions.iter() {
+        let (prefix, indent) = match &region.name {
+            Some(name) => {
+                lines.push(format!("    subgraph \"cluster_{}\" {{", name));
+                lines.push(format!("        label=\"{}\";", name));
+                (format!("{}.", name), "        ")
+            }
+            None => (String::new(), "    "),
+        };
+
+        let diagram = RegionDiagram::new(region, prefix);
+
+        lines.push(format!(
+            "{}\"{}s\" [shape=circle size=2 color=\"black\" style=filled label=\"\"]",
+            indent, diagram.prefix
+        ));
+        write_states(&diagram, &mut lines, None, indent);
+        if region.transitions.iter().any(|t| t.in_state.is_none()) {
+            lines.push(format!(
+                "{}{} [shape=box style=dashed label=\"_\"]",
+                indent,
+                node(&diagram, "_")
+            ));
+        }
+
+        let (start_node, start_clip) = endpoint(&diagram, &region.start.to_string(), "lhead");
+        lines.push(format!(
+            "{}\"{}s\" -> {}{};",
+            indent,
+            diagram.prefix,
+            start_node,
+            start_clip.map_or(String::new(), |clip| format!(" [{}]", clip)),
+        ));
+
+        for transition in region.transitions.iter() {
+            let index = labels.len() + 1;
+            labels.push(transition_label(transition));
+
+            let (from, ltail) = match &transition.in_state {
+                Some(in_state) => endpoint(&diagram, &in_state.ident.to_string(), "ltail"),
+                None => (node(&diagram, "_"), None),
+            };
+            let mut attributes = vec!["color=blue".to_string(), format!("label={}", index)];
+            let to = match &transition.out_state {
+                Some(out_state) => {
+                    let (to, lhead) = endpoint(&diagram, &out_state.to_string(), "lhead");
+                    attributes.extend(ltail);
+                    attributes.extend(lhead);
+                    to
+                }
+                None => {
+                    attributes.push("style=dashed".to_string());
+                    from.clone()
+                }
+            };
+
+            lines.push(format!(
+                "{}{} -> {} [{}];",
+                indent,
+                from,
+                to,
+                attributes.join(" ")
+            ));
+        }
+
+        if region.name.is_some() {
+            lines.push("    }".to_string());
+        }
+    }
+
+    let labels = labels
+        .iter()
+        .enumerate()
+        .map(|(i, label)| format!("    {0} [shape=box label=\"{0}: {1}\"]", i + 1, label))
+        .collect::<Vec<String>>();
+
+    format!(
+        "digraph G {{
+    rankdir=\"LR\";
+    compound=true;
+    node [fontname=Arial];
+    edge [fontname=Arial];
+
+{}
+
+{}
+}}
+",
+        lines.join("\n"),
+        labels.join("\n"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagram::fixtures::{self, parse};
+
+    #[test]
+    fn transitions() {
+        let dot = generate_diagram(&parse(quote! {
+            transitions: {
+                *State1 + Event1(MyEventData { x: 1, .. }) [guard] / action(); = State2(1),
+                State2(u32) + Event2 [*state > 0] = State1,
+            }
+        }));
+
+        assert_eq!(
+            dot,
+            r##"digraph G {
+    rankdir="LR";
+    compound=true;
+    node [fontname=Arial];
+    edge [fontname=Arial];
+
+    "s" [shape=circle size=2 color="black" style=filled label=""]
+    "State1" [shape=box color="red" fillcolor="#ffbb33" style=filled label="State1"]
+    "State2" [shape=box color="red" fillcolor="#ffbb33" style=filled label="State2 (u32)"]
+    "s" -> "State1";
+    "State1" -> "State2" [color=blue label=1];
+    "State2" -> "State1" [color=blue label=2];
+
+    1 [shape=box label="1: State1 + Event1(MyEventData { x : 1 , .. }) [guard] / action () ; = State2(1)"]
+    2 [shape=box label="2: State2 (u32) + Event2 [* state > 0] = State1"]
+}
+"##
+        );
+    }
+
+    #[test]
+    fn wildcards_and_internal_transitions() {
+        let dot = generate_diagram(&parse(quote! {
+            transitions: {
+                *State1 + Event1 = State2,
+                State2 + Event2 / count();,
+                _ + Reset = State1,
+                _ + Ping / pong();,
+            }
+        }));
+
+        assert_eq!(
+            dot,
+            r##"digraph G {
+    rankdir="LR";
+    compound=true;
+    node [fontname=Arial];
+    edge [fontname=Arial];
+
+    "s" [shape=circle size=2 color="black" style=filled label=""]
+    "State1" [shape=box color="red" fillcolor="#ffbb33" style=filled label="State1"]
+    "State2" [shape=box color="red" fillcolor="#ffbb33" style=filled label="State2"]
+    "_" [shape=box style=dashed label="_"]
+    "s" -> "State1";
+    "State1" -> "State2" [color=blue label=1];
+    "State2" -> "State2" [color=blue label=2 style=dashed];
+    "_" -> "State1" [color=blue label=3];
+    "_" -> "_" [color=blue label=4 style=dashed];
+
+    1 [shape=box label="1: State1 + Event1 = State2"]
+    2 [shape=box label="2: State2 + Event2 / count () ;"]
+    3 [shape=box label="3: _ + Reset = State1"]
+    4 [shape=box label="4: _ + Ping / pong () ;"]
+}
+"##
+        );
+    }
+
+    #[test]
+    fn composite_states_and_regions() {
+        let dot = generate_diagram(&fixtures::composite_states_and_regions());
+
+        assert_eq!(
+            dot,
+            r##"digraph G {
+    rankdir="LR";
+    compound=true;
+    node [fontname=Arial];
+    edge [fontname=Arial];
+
+    subgraph "cluster_Main" {
+        label="Main";
+        "Main.s" [shape=circle size=2 color="black" style=filled label=""]
+        subgraph "cluster_Main.Operational" {
+            label="Operational";
+            "Main.Idle" [shape=box color="red" fillcolor="#ffbb33" style=filled label="Idle"]
+            "Main.Busy" [shape=box color="red" fillcolor="#ffbb33" style=filled label="Busy"]
+        }
+        "Main.Error" [shape=box color="red" fillcolor="#ffbb33" style=filled label="Error"]
+        "Main.s" -> "Main.Idle" [lhead="cluster_Main.Operational"];
+        "Main.Idle" -> "Main.Error" [color=blue label=1 ltail="cluster_Main.Operational"];
+        "Main.Idle" -> "Main.Busy" [color=blue label=2];
+        "Main.Error" -> "Main.Idle" [color=blue label=3 lhead="cluster_Main.Operational"];
+    }
+    subgraph "cluster_Power" {
+        label="Power";
+        "Power.s" [shape=circle size=2 color="black" style=filled label=""]
+        "Power.On" [shape=box color="red" fillcolor="#ffbb33" style=filled label="On"]
+        "Power.Off" [shape=box color="red" fillcolor="#ffbb33" style=filled label="Off"]
+        "Power.s" -> "Power.On";
+        "Power.On" -> "Power.Off" [color=blue label=4];
+    }
+
+    1 [shape=box label="1: Operational + Fault = Error"]
+    2 [shape=box label="2: Idle + Start = Busy"]
+    3 [shape=box label="3: Error + Reset = Operational(H)"]
+    4 [shape=box label="4: On + Off = Off"]
+}
+"##
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use crate::parser::{ParsedStateMachine, StateMachine};
+use crate::Error;
+use std::path::PathBuf;
+use syn::LitStr;
+
+/// Reads the DSL of a state machine from `path`, relative to the crate using the macro, and
+/// validates it. Returns the state machine and the full path of the file.
+pub fn read(path: &LitStr) -> syn::Result<(ParsedStateMachine, String)> {
+    let mut full_path =
+        PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string()));
+    full_path.push(path.value());
+
+    let source = std::fs::read_to_string(&full_path).map_err(|e| {
+        syn::Error::new(
+            path.span(),
+            format!("Failed to read {}: {}", full_path.display(), e),
+        )
+    })?;
+
+    let error = match syn::parse_str::<StateMachine>(&source).and_then(ParsedStateMachine::new) {
+        Ok(sm) => return Ok((sm, full_path.display().to_string())),
+        Err(error) => error,
+    };
+
+    // Only once the DSL is known to be wrong, it is checked again to locate the errors
+    #[cfg(feature = "locations")]
+    let errors = check(&source).err().unwrap_or_else(|| error.into());
+    #[cfg(not(feature = "locations"))]
+    let errors = Error::from(error);
+
+    // The errors are reported on the path, telling where they are in the file
+    let mut errors = errors.errors().map(|(location, message)| {
+        let message = match location {
+            Some((line, column)) => {
+                format!("{}:{}:{}: {}", path.value(), line, column, message)
+            }
+            None => format!("{}: {}", path.value(), message),
+        };
+        syn::Error::new(path.span(), message)
+    });
+    let mut error = errors.next().expect("failed checks have errors");
+    error.extend(errors);
+    Err(error)
+}
+
+/// Parses and validates the DSL, returning the errors with their line and column.
+///
+/// The spans of the compiler do not know where tokens parsed from a string are, so this is done
+/// with the implementation of spans of `proc_macro2` instead, which does.
+#[cfg(feature = "locations")]
+fn check(source: &str) -> Result<(), Error> {
+    proc_macro2::fallback::force();
+
+    let result = syn::parse_str::<StateMachine>(source)
+        .and_then(ParsedStateMachine::new)
+        .map(|_| ())
+        .map_err(Error::from);
+
+    proc_macro2::fallback::unforce();
+    result
+}
+
+#[cfg(all(test, feature = "locations"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_locations() {
+        let error = check(
+            "events: { Open, Close },
+transitions: {
+    *Closed + Open = Opened,
+    Opened + Lock = Closed,
+}",
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.errors().collect::<Vec<_>>(),
+            vec![(Some((4, 14)), "Event Lock is not declared in events.")]
+        );
+    }
+
+    #[test]
+    fn errors_without_location() {
+        let error = check("transitions: { Closed + Open = Opened }").unwrap_err();
+
+        assert_eq!(
+            error.errors().collect::<Vec<_>>(),
+            vec![(
+                None,
+                "No starting state defined, indicate the starting state with a *."
+            )]
+        );
+    }
+}
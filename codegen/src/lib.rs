@@ -0,0 +1,283 @@
+//! # smlang-codegen
+//!
+//! The parser and code generator of the `smlang` state machine language, shared by the
+//! `statemachine!` macro and build scripts.
+//!
+//! Generating the code from a `build.rs` makes it visible to IDEs and easy to read:
+//!
+//! ```no_run
+//! // In the `main` of build.rs
+//! let dsl = std::fs::read_to_string("machines/motor.sm").unwrap();
+//! let code = smlang_codegen::generate(&dsl).unwrap_or_else(|e| panic!("{}", e));
+//!
+//! let out_dir = std::env::var("OUT_DIR").unwrap();
+//! std::fs::write(format!("{}/motor.rs", out_dir), code).unwrap();
+//! println!("cargo:rerun-if-changed=machines/motor.sm");
+//! ```
+//!
+//! The generated file is then included where the state machine is used, in a crate depending
+//! on `smlang`:
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/motor.rs"));
+//! ```
+
+#![recursion_limit = "512"]
+
+#[doc(hidden)]
+pub mod codegen;
+#[doc(hidden)]
+pub mod diagram;
+#[cfg(feature = "graphviz")]
+mod diagramgen;
+#[doc(hidden)]
+pub mod file;
+#[cfg(feature = "mermaid")]
+mod mermaidgen;
+#[doc(hidden)]
+pub mod parser;
+#[cfg(feature = "plantuml")]
+mod plantumlgen;
+#[cfg(feature = "scxml")]
+mod scxmlgen;
+
+use proc_macro2::TokenStream;
+use std::fmt;
+
+/// Errors in the definition of a state machine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    errors: Vec<(Option<(usize, usize)>, String)>,
+}
+
+impl Error {
+    /// The errors, with their line and column in the definition starting at 1, if they have a
+    /// place in it.
+    pub fn errors(&self) -> impl Iterator<Item = (Option<(usize, usize)>, &str)> {
+        self.errors
+            .iter()
+            .map(|(location, message)| (*location, message.as_str()))
+    }
+}
+
+impl From<syn::Error> for Error {
+    fn from(error: syn::Error) -> Self {
+        let errors = error
+            .into_iter()
+            .map(|error| {
+                // Errors without a place in the definition are on the call site, which has no
+                // source text
+                #[cfg(feature = "locations")]
+                let location = {
+                    let span = error.span();
+                    span.source_text()
+                        .map(|_| (span.start().line, span.start().column + 1))
+                };
+                #[cfg(not(feature = "locations"))]
+                let location = None;
+                (location, error.to_string())
+            })
+            .collect();
+        Error { errors }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (location, message)) in self.errors.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            match location {
+                Some((line, column)) => write!(f, "{}:{}: {}", line, column, message)?,
+                None => write!(f, "{}", message)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Generates the formatted Rust code of a state machine from its definition, in the syntax of
+/// the `statemachine!` macro.
+///
+/// Diagrams are written as with the macro, relative to the `CARGO_MANIFEST_DIR` of the build
+/// script.
+pub fn generate(input: &str) -> Result<String, Error> {
+    let sm = syn::parse_str::<parser::StateMachine>(input)?;
+    let sm = parser::ParsedStateMachine::new(sm)?;
+
+    let (code, error) = expand(&sm);
+    if let Some(error) = error {
+        return Err(error.into());
+    }
+
+    let file = syn::parse2::<syn::File>(code)?;
+    Ok(prettyplease::unparse(&file))
+}
+
+/// Generates the code of a validated state machine with the constants of its diagrams, and
+/// writes its diagram. The error of a diagram which could not be written is returned with the
+/// code, which is still complete.
+#[doc(hidden)]
+pub fn expand(sm: &parser::ParsedStateMachine) -> (TokenStream, Option<syn::Error>) {
+    let mut code = codegen::generate_code(sm);
+
+    // Generate the diagrams of the statemachine in the enabled formats, a diagram path without
+    // them is reported when writing it.
+    let diagrams = diagram::generate(sm);
+    let error = diagram::write(sm, &diagrams).err();
+
+    for diagram::Diagram {
+        constant,
+        doc,
+        text,
+        ..
+    } in diagrams
+    {
+        let name = codegen::generated_const(sm, constant);
+        code.extend(quote::quote! {
+            #[doc = #doc]
+            pub const #name: &str = #text;
+        });
+    }
+
+    (code, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formatted_code() {
+        let code = generate(
+            "transitions: {
+                *Idle + Start = Running,
+                Running + Stop = Idle,
+            }",
+        )
+        .unwrap();
+
+        assert!(code.contains("\npub enum States {\n    Idle,\n    Running,\n}\n"));
+        assert!(code.contains("\npub struct StateMachine {\n"));
+    }
+
+    /// The code and diagrams of a state machine.
+    fn output(input: &str) -> String {
+        let sm = syn::parse_str::<parser::StateMachine>(input).unwrap();
+        let sm = parser::ParsedStateMachine::new(sm).unwrap();
+
+        let mut output = codegen::generate_code(&sm).to_string();
+        for diagram in diagram::generate(&sm) {
+            output += &diagram.text;
+        }
+        output
+    }
+
+    #[test]
+    fn deterministic_output() {
+        let input = "
+            states: {
+                Working { states: { *Heating, Cooling, Venting } },
+            },
+            transitions: {
+                *Zeta + Go = Working,
+                Heating + Cool = Cooling,
+                Cooling + Vent = Venting,
+                Venting + Stop = Alpha,
+                Alpha + Resume = Working(H),
+                Alpha + Reset = Mid,
+                Mid + Go = Zeta,
+                Working + Fault = Mid,
+                _ + Off = Zeta,
+            }";
+
+        // Each run hashes differently, so any order depending on hashing would show up
+        let first = output(input);
+        for _ in 0..20 {
+            assert_eq!(output(input), first);
+        }
+
+        // States and their arms follow the order of the DSL
+        assert!(
+            first.contains("pub enum States { Zeta , Heating , Cooling , Venting , Alpha , Mid }")
+        );
+        let arm = |state: &str| first.find(&format!("States :: {} =>", state)).unwrap();
+        assert!(arm("Zeta") < arm("Heating") && arm("Venting") < arm("Alpha"));
+        assert!(arm("Alpha") < arm("Mid"));
+    }
+
+    #[test]
+    #[cfg(feature = "locations")]
+    fn error_locations() {
+        let error = generate(
+            "events: { Start },
+transitions: {
+    *Idle + Start = Running,
+    Running + Stop = Idle,
+}",
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "4:15: Event Stop is not declared in events."
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "locations")]
+    #[cfg(not(feature = "graphviz"))]
+    fn diagram_without_feature() {
+        let error = generate(
+            "diagram: \"target/diagrams/motor.dot\",
+transitions: {
+    *Idle + Start = Running,
+    Running + Stop = Idle,
+}",
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "1:10: Writing .dot diagrams requires the `graphviz` feature."
+        );
+    }
+
+    #[test]
+    fn allowed_shadowed_transitions() {
+        let input = "transitions: {
+            *Idle + Start [ctx.ready()] = Running,
+            Idle + Start [ctx.ready()] = Paused,
+            Running + Stop = Idle,
+            Paused + Stop = Idle,
+        }";
+        assert!(generate(input).is_err());
+
+        // The guard may have side effects, so the second transition can be intended
+        assert!(generate(&format!("#![allow(shadowed_transitions)] {}", input)).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "locations")]
+    fn misspelled_events() {
+        let error = generate(
+            "events: { Start, Stop, Pause },
+transitions: {
+    *Idle + Strat = Running,
+    Running + Pasue = Paused,
+    Paused + Restart = Idle,
+}",
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "3:13: Event Strat is not declared in events, did you mean Start?\n\
+             4:15: Event Pasue is not declared in events, did you mean Pause?\n\
+             5:14: Event Restart is not declared in events."
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use crate::diagram::{container, transition_label, RegionDiagram};
+use crate::parser::*;
+use quote::quote;
+
+/// Escapes text for use in a Mermaid label.
+fn escape(v: String) -> String {
+    v.replace('#', "#35;")
+        .replace('"', "#quot;")
+        .replace(';', "#59;")
+}
+
+/// Writes the states and transitions inside of `parent`, or at the top of the region for
+/// `None`.
+fn write(diagram: &RegionDiagram, out: &mut Vec<String>, parent: Option<&String>, indent: &str) {
+    match parent {
+        Some(parent) => out.push(format!(
+            "{}[*] --> {}",
+            indent,
+            diagram.name(&diagram.region.initial_states[parent])
+        )),
+        None => out.push(format!(
+            "{}[*] --> {}",
+            indent,
+            diagram.name(&diagram.region.start.to_string())
+        )),
+    }
+
+    for state in diagram
+        .states
+        .iter()
+        .filter(|s| diagram.region.parents.get(*s) == parent)
+    {
+        if diagram.region.is_composite(state) {
+            if !diagram.prefix.is_empty() {
+                out.push(format!(
+                    "{}state \"{}\" as {}",
+                    indent,
+                    state,
+                    diagram.name(state)
+                ));
+            }
+            out.push(format!("{}state {} {{", indent, diagram.name(state)));
+            write(diagram, out, Some(state), &format!("{}    ", indent));
+            out.push(format!("{}}}", indent));
+        } else {
+            let variant = &diagram.region.states[state];
+            let label = escape(quote! { #variant }.to_string());
+            if label != *state || !diagram.prefix.is_empty() {
+                out.push(format!(
+                    "{}state \"{}\" as {}",
+                    indent,
+                    label,
+                    diagram.name(state)
+                ));
+            }
+        }
+    }
+
+    if parent.is_none()
+        && diagram
+            .region
+            .transitions
+            .iter()
+            .any(|t| t.in_state.is_none())
+    {
+        out.push(format!("{}state \"_\" as {}", indent, diagram.name("any")));
+    }
+
+    for transition in diagram
+        .region
+        .transitions
+        .iter()
+        .filter(|t| container(diagram.region, t).as_ref() == parent)
+    {
+        let source = match &transition.in_state {
+            Some(in_state) => diagram.name(&in_state.ident.to_string()),
+            None => diagram.name("any"),
+        };
+        let label = escape(transition_label(transition));
+        match &transition.out_state {
+            Some(out_state) => out.push(format!(
+                "{}{} --> {} : {}",
+                indent,
+                source,
+                diagram.name(&out_state.to_string()),
+                label
+            )),
+            // Internal transitions are listed in the state they happen in
+            None => out.push(format!("{}{} : {}", indent, source, label)),
+        }
+    }
+}
+
+/// Generates a string containing Mermaid `stateDiagram-v2` syntax for a statemachine diagram.
+///
+/// Wildcard transitions are drawn once from a `_` state, internal transitions are listed inside
+/// of their state, and regions are drawn as composite states.
+pub fn generate_diagram(sm: &ParsedStateMachine) -> String {
+    let mut lines = vec!["stateDiagram-v2".to_string()];
+
+    for region in sm.regions.iter() {
+        let diagram = RegionDiagram::new(
+            region,
+            region
+                .name
+                .as_ref()
+                .map_or(String::new(), |name| format!("{}_", name)),
+        );
+
+        match &region.name {
+            Some(name) => {
+                lines.push(format!("    state {} {{", name));
+                write(&diagram, &mut lines, None, "        ");
+                lines.push("    }".to_string());
+            }
+            None => write(&diagram, &mut lines, None, "    "),
+        }
+    }
+
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagram::fixtures;
+
+    #[test]
+    fn transitions() {
+        let mermaid = generate_diagram(&fixtures::transitions());
+
+        assert_eq!(
+            mermaid,
+            r#"stateDiagram-v2
+    [*] --> State1
+    state "State2 (u32)" as State2
+    state "_" as any
+    State1 --> State2 : Event1(MyEventData { x : 1 , .. }) [guard] / action ()
+    State2 --> State1 : Event2 [* state > 0 && ready ()] / { a () #59; b () }
+    State2 : Event3 / count ()
+    any --> State1 : Reset
+"#
+        );
+    }
+
+    #[test]
+    fn composite_states_and_regions() {
+        let mermaid = generate_diagram(&fixtures::composite_states_and_regions());
+
+        assert_eq!(
+            mermaid,
+            r#"stateDiagram-v2
+    state Main {
+        [*] --> Main_Operational
+        state "Operational" as Main_Operational
+        state Main_Operational {
+            [*] --> Main_Idle
+            state "Idle" as Main_Idle
+            state "Busy" as Main_Busy
+            Main_Idle --> Main_Busy : Start
+        }
+        state "Error" as Main_Error
+        Main_Operational --> Main_Error : Fault
+        Main_Error --> Main_Operational : Reset (H)
+    }
+    state Power {
+        [*] --> Power_On
+        state "On" as Power_On
+        state "Off" as Power_Off
+        Power_On --> Power_Off : Off
+    }
+"#
+        );
+    }
+}
@@ -0,0 +1,1375 @@
+use indexmap::{IndexMap, IndexSet};
+use proc_macro2::Span;
+use quote::ToTokens;
+use syn::{
+    braced, bracketed, parenthesized, parse, parse::discouraged::Speculative,
+    punctuated::Punctuated, token, Attribute, Expr, Ident, LitBool, LitStr, Pat, Stmt, Token, Type,
+    Variant,
+};
+
+#[derive(Debug)]
+pub struct StateMachine {
+    pub region: Region,
+    pub regions: Vec<Region>,
+    pub states_attrs: Vec<Attribute>,
+    pub guard_error: Option<Type>,
+    pub action_error: Option<Type>,
+    pub temporary_context: Option<Type>,
+    pub events: Option<Vec<Variant>>,
+    pub events_attrs: Vec<Attribute>,
+    pub name: Option<Ident>,
+    pub context_type: Option<Type>,
+    pub diagram: Option<LitStr>,
+    /// The checks turned off with `#![allow(...)]`.
+    pub allowed: Vec<Ident>,
+}
+
+impl StateMachine {
+    fn new() -> Self {
+        StateMachine {
+            region: Region::new(None),
+            regions: Vec::new(),
+            states_attrs: Vec::new(),
+            guard_error: None,
+            action_error: None,
+            temporary_context: None,
+            events: None,
+            events_attrs: Vec::new(),
+            name: None,
+            context_type: None,
+            diagram: None,
+            allowed: Vec::new(),
+        }
+    }
+
+    pub fn add_region(&mut self, region: Region) {
+        self.regions.push(region);
+    }
+
+    pub fn add_state_attrs(&mut self, attrs: Vec<Attribute>) {
+        self.states_attrs.extend(attrs);
+    }
+
+    pub fn add_event_attrs(&mut self, attrs: Vec<Attribute>) {
+        self.events_attrs.extend(attrs);
+    }
+
+    /// Whether the check named `lint` is turned off.
+    pub fn allows(&self, lint: &str) -> bool {
+        self.allowed.iter().any(|allowed| allowed == lint)
+    }
+
+    /// The transitions of all regions, wildcards included.
+    fn transitions(&self) -> impl Iterator<Item = &StateTransition> {
+        std::iter::once(&self.region)
+            .chain(self.regions.iter())
+            .flat_map(|r| r.transitions.iter().chain(r.wildcards.iter()))
+    }
+}
+
+/// The checks which can be turned off with `#![allow(...)]`.
+const LINTS: &[&str] = &[
+    "shadowed_transitions",
+    "unreachable_states",
+    "unused_events",
+];
+
+#[derive(Debug)]
+pub struct Region {
+    pub name: Option<Ident>,
+    pub transitions: Vec<StateTransition>,
+    pub wildcards: Vec<StateTransition>,
+    pub states: Vec<StateDefinition>,
+}
+
+impl Region {
+    pub fn new(name: Option<Ident>) -> Self {
+        Region {
+            name,
+            transitions: Vec::new(),
+            wildcards: Vec::new(),
+            states: Vec::new(),
+        }
+    }
+
+    pub fn add_transition(&mut self, transition: StateTransition) {
+        self.transitions.push(transition);
+    }
+
+    pub fn add_wildcard(&mut self, transition: StateTransition) {
+        self.wildcards.push(transition);
+    }
+
+    pub fn add_state_definition(&mut self, state: StateDefinition) {
+        self.states.push(state);
+    }
+}
+
+#[derive(Debug)]
+pub struct ParsedStateMachine {
+    pub regions: Vec<ParsedRegion>,
+    pub states_attrs: Vec<Attribute>,
+    pub guard_error: Option<Type>,
+    pub action_error: Option<Type>,
+    pub temporary_context: Option<Type>,
+    pub events: Option<Vec<Variant>>,
+    pub events_attrs: Vec<Attribute>,
+    pub name: Option<Ident>,
+    pub context_type: Option<Type>,
+    pub diagram: Option<LitStr>,
+    /// The declared events which no transition handles, unless allowed.
+    pub unused_events: Vec<Ident>,
+}
+
+impl ParsedStateMachine {
+    pub fn new(sm: StateMachine) -> parse::Result<Self> {
+        if sm.events.is_none() && !sm.events_attrs.is_empty() {
+            return Err(parse::Error::new(
+                sm.events_attrs[0].pound_token.span,
+                "events_attr can only be used together with events.",
+            ));
+        }
+
+        // With declared events, all events used in transitions must be among them
+        if let Some(events) = &sm.events {
+            for event in events.iter() {
+                if events.iter().filter(|e| e.ident == event.ident).count() > 1 {
+                    return Err(parse::Error::new(
+                        event.ident.span(),
+                        format!("Event {} is declared more than once.", event.ident),
+                    ));
+                }
+            }
+
+            let mut errors = sm
+                .transitions()
+                .filter(|t| !events.iter().any(|e| e.ident == t.event))
+                .map(|t| {
+                    let message = match closest(&t.event, events.iter().map(|e| &e.ident)) {
+                        Some(event) => format!(
+                            "Event {} is not declared in events, did you mean {}?",
+                            t.event, event
+                        ),
+                        None => format!("Event {} is not declared in events.", t.event),
+                    };
+                    parse::Error::new(t.event.span(), message)
+                });
+            if let Some(mut error) = errors.next() {
+                error.extend(errors);
+                return Err(error);
+            }
+        }
+
+        // Declared events which no transition handles are most likely a mistake
+        let unused_events = match &sm.events {
+            Some(events) if !sm.allows("unused_events") => events
+                .iter()
+                .filter(|e| !sm.transitions().any(|t| t.event == e.ident))
+                .map(|e| e.ident.clone())
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let allow_unreachable_states = sm.allows("unreachable_states");
+        let allow_shadowed_transitions = sm.allows("shadowed_transitions");
+
+        let regions = if sm.regions.is_empty() {
+            vec![ParsedRegion::new(sm.region, allow_shadowed_transitions)?]
+        } else {
+            // The states block comes before the transitions
+            let stray = sm
+                .region
+                .states
+                .first()
+                .map(|s| s.ident.span())
+                .or_else(|| {
+                    sm.region
+                        .transitions
+                        .first()
+                        .and_then(|t| t.in_state.as_ref())
+                        .map(|s| s.ident.span())
+                })
+                .or_else(|| sm.region.wildcards.first().map(|t| t.event.span()));
+            if let Some(stray) = stray {
+                return Err(parse::Error::new(
+                    stray,
+                    "When regions are used, all transitions and states must be inside of them.",
+                ));
+            }
+
+            let mut regions: Vec<ParsedRegion> = Vec::new();
+            for region in sm.regions.into_iter() {
+                let region = ParsedRegion::new(region, allow_shadowed_transitions)?;
+                let name = region.name.as_ref().unwrap();
+                if regions.iter().any(|r| r.name == region.name) {
+                    return Err(parse::Error::new(
+                        name.span(),
+                        format!("Region {} is defined more than once.", name),
+                    ));
+                }
+
+                // The state of each region is in a field named after it
+                let field = crate::codegen::snake_case(&name.to_string());
+                if syn::parse_str::<Ident>(&field).is_err() {
+                    return Err(parse::Error::new(
+                        name.span(),
+                        format!(
+                            "Region {} would be stored in the field `{}`, which is a keyword. Rename the region.",
+                            name, field
+                        ),
+                    ));
+                }
+                regions.push(region);
+            }
+            regions
+        };
+
+        // States which can never be entered are most likely a mistake
+        if !allow_unreachable_states {
+            let mut errors = regions.iter().flat_map(|region| {
+                region.unreachable_states().into_iter().map(move |state| {
+                    parse::Error::new(
+                        region.declaration_span(&state),
+                        format!(
+                            "State {} is unreachable, no transitions lead to it from the starting state. Allow unreachable states with `#![allow(unreachable_states)]`.",
+                            state
+                        ),
+                    )
+                })
+            });
+            if let Some(mut error) = errors.next() {
+                error.extend(errors);
+                return Err(error);
+            }
+        }
+
+        Ok(ParsedStateMachine {
+            regions,
+            states_attrs: sm.states_attrs,
+            guard_error: sm.guard_error,
+            action_error: sm.action_error,
+            temporary_context: sm.temporary_context,
+            events: sm.events,
+            events_attrs: sm.events_attrs,
+            name: sm.name,
+            context_type: sm.context_type,
+            diagram: sm.diagram,
+            unused_events,
+        })
+    }
+
+    /// Whether the state machine is made of named, orthogonal regions.
+    pub fn has_regions(&self) -> bool {
+        self.regions.iter().any(|r| r.name.is_some())
+    }
+}
+
+/// A validated region. The maps keep the order of the DSL so that the generated code and
+/// diagrams are the same on every build.
+#[derive(Debug)]
+pub struct ParsedRegion {
+    pub name: Option<Ident>,
+    /// The state marked with a * in the transitions, which may be a composite state.
+    pub start: Ident,
+    /// The state the region starts in, entering the sub-states of `start`.
+    pub starting_state: Variant,
+
+    /// The states, in order of first use in the transitions, followed by the ones only used in
+    /// the state definitions.
+    pub states: IndexMap<String, Variant>,
+    /// The transitions of each state in order of priority: its own in declaration order, then
+    /// the ones of its parents from the innermost out, and finally the wildcards.
+    pub states_events_mapping: IndexMap<String, Vec<StateTransition>>,
+    /// The transitions as declared, followed by the wildcards.
+    pub transitions: Vec<StateTransition>,
+    pub state_definitions: IndexMap<String, StateDefinition>,
+    pub parents: IndexMap<String, String>,
+    pub initial_states: IndexMap<String, String>,
+    pub history_states: IndexSet<String>,
+}
+
+impl ParsedRegion {
+    pub fn new(sm: Region, allow_shadowed_transitions: bool) -> parse::Result<Self> {
+        // Regions report errors on their name, the state machine on the whole macro
+        let span = sm
+            .name
+            .as_ref()
+            .map_or(Span::call_site(), |name| name.span());
+
+        // Check the initial state definition
+        if let Some(wildcard) = sm.wildcards.iter().find(|t| t.start) {
+            return Err(parse::Error::new(
+                wildcard.event.span(),
+                "The starting state cannot be a wildcard, mark a named state with a * instead.",
+            ));
+        }
+        let starts: Vec<&Variant> = sm
+            .transitions
+            .iter()
+            .filter(|t| t.start)
+            .filter_map(|t| t.in_state.as_ref())
+            .collect();
+        let start =
+            match starts[..] {
+                [] => {
+                    return Err(parse::Error::new(
+                        span,
+                        "No starting state defined, indicate the starting state with a *.",
+                    ))
+                }
+                [start] => start.ident.clone(),
+                [_, second, ..] => return Err(parse::Error::new(
+                    second.ident.span(),
+                    "More than one starting state defined (indicated with *), remove duplicates.",
+                )),
+            };
+
+        // Flatten the state definitions, remembering the hierarchy
+        let mut state_definitions = IndexMap::new();
+        let mut parents = IndexMap::new();
+        let mut initial_states = IndexMap::new();
+        for def in sm.states.into_iter() {
+            if def.start {
+                return Err(parse::Error::new(
+                    def.ident.span(),
+                    "Only sub-states can be marked with a *, indicate the starting state in the transitions.",
+                ));
+            }
+            add_state_definition(
+                def,
+                &mut state_definitions,
+                &mut parents,
+                &mut initial_states,
+            )?;
+        }
+
+        let mut states = IndexMap::<String, Variant>::new();
+        let mut own_transitions = IndexMap::<String, Vec<StateTransition>>::new();
+
+        //create out state variant, might get overwritten by in state
+        let add_out_state = |states: &mut IndexMap<String, Variant>,
+                             transition: &StateTransition|
+         -> parse::Result<()> {
+            if let Some(state) = transition.out_state.clone() {
+                let s = state.to_string();
+                if initial_states.contains_key(&s) {
+                    if let Some(expr) = &transition.out_state_data_expr {
+                        return Err(parse::Error::new_spanned(
+                            expr,
+                            format!("State {} has sub-states and cannot have data.", s),
+                        ));
+                    }
+                } else if !states.contains_key(&s) {
+                    states.insert(
+                        s.clone(),
+                        Variant {
+                            attrs: Vec::new(),
+                            ident: state,
+                            fields: syn::Fields::Unit,
+                            discriminant: None,
+                        },
+                    );
+                }
+            }
+            Ok(())
+        };
+
+        for transition in sm.transitions.iter() {
+            //the data type of a state is declared once, in the source of any of its transitions
+            let state = transition.in_state.clone().expect("no wildcards");
+            let s = state.ident.to_string();
+            if initial_states.contains_key(&s) {
+                if state.fields != syn::Fields::Unit {
+                    return Err(parse::Error::new_spanned(
+                        &state.fields,
+                        format!("State {} has sub-states and cannot have data.", s),
+                    ));
+                }
+            } else {
+                let declared = states
+                    .get(&s)
+                    .map(|declared| &declared.fields)
+                    .filter(|fields| **fields != syn::Fields::Unit);
+                match declared {
+                    Some(fields)
+                        if state.fields != syn::Fields::Unit && *fields != state.fields =>
+                    {
+                        let mut error = parse::Error::new_spanned(
+                            &state.fields,
+                            format!(
+                                "State {} is declared with data {} here, which conflicts with its earlier declaration. The data of a state only needs to be declared once.",
+                                s,
+                                state.fields.to_token_stream()
+                            ),
+                        );
+                        error.combine(parse::Error::new_spanned(
+                            fields,
+                            format!(
+                                "State {} is first declared with data {} here.",
+                                s,
+                                fields.to_token_stream()
+                            ),
+                        ));
+                        return Err(error);
+                    }
+                    Some(_) => {}
+                    None => {
+                        states.insert(s.clone(), state);
+                    }
+                }
+            }
+
+            own_transitions
+                .entry(s)
+                .or_default()
+                .push(transition.clone());
+
+            add_out_state(&mut states, transition)?;
+        }
+
+        for wc in sm.wildcards.iter() {
+            add_out_state(&mut states, wc)?;
+        }
+
+        //states only mentioned in the definitions still need a variant, and the definitions
+        //may declare the data of states
+        for def in state_definitions.values() {
+            let s = def.ident.to_string();
+            if initial_states.contains_key(&s) {
+                continue;
+            }
+            match states.get_mut(&s) {
+                None => {
+                    states.insert(
+                        s,
+                        Variant {
+                            attrs: Vec::new(),
+                            ident: def.ident.clone(),
+                            fields: def.fields.clone(),
+                            discriminant: None,
+                        },
+                    );
+                }
+                Some(state) if state.fields == syn::Fields::Unit => {
+                    state.fields = def.fields.clone();
+                }
+                Some(state) if def.fields != syn::Fields::Unit && def.fields != state.fields => {
+                    let mut error = parse::Error::new_spanned(
+                        &def.fields,
+                        format!(
+                            "State {} is declared with data {} in the states block, which conflicts with its declaration in the transitions. The data of a state only needs to be declared once.",
+                            s,
+                            def.fields.to_token_stream()
+                        ),
+                    );
+                    error.combine(parse::Error::new_spanned(
+                        &state.fields,
+                        format!(
+                            "State {} is declared with data {} here.",
+                            s,
+                            state.fields.to_token_stream()
+                        ),
+                    ));
+                    return Err(error);
+                }
+                Some(_) => {}
+            }
+        }
+
+        //the data of destination states can only be set for states which have data
+        for transition in sm.transitions.iter().chain(sm.wildcards.iter()) {
+            if let (Some(out_state), Some(expr)) =
+                (&transition.out_state, &transition.out_state_data_expr)
+            {
+                let s = out_state.to_string();
+                if states
+                    .get(&s)
+                    .is_some_and(|state| state.fields == syn::Fields::Unit)
+                {
+                    return Err(parse::Error::new_spanned(
+                        expr,
+                        format!(
+                            "State {} has no data, declare its data type in the states block, as in `states: {{ {}(Type) }}`.",
+                            s, s
+                        ),
+                    ));
+                }
+            }
+        }
+
+        // Every state handles its own transitions first, then the ones of its parents from the
+        // innermost out, and finally the wildcards
+        let mut states_events_mapping = IndexMap::<String, Vec<StateTransition>>::new();
+        for s in states.keys() {
+            let mut transitions = Vec::new();
+            let mut state = Some(s);
+            while let Some(current) = state {
+                if let Some(own) = own_transitions.get(current) {
+                    transitions.extend(own.iter().cloned());
+                }
+                state = parents.get(current);
+            }
+            transitions.extend(sm.wildcards.iter().cloned());
+
+            if !transitions.is_empty() {
+                states_events_mapping.insert(s.clone(), transitions);
+            }
+        }
+
+        // Transitions which are always handled by an earlier one are most likely a mistake
+        let mut errors = Vec::new();
+        if !allow_shadowed_transitions {
+            for (i, later) in sm.transitions.iter().enumerate() {
+                let state = &later.in_state.as_ref().expect("no wildcards").ident;
+                let earlier = sm.transitions[..i].iter().find(|t| {
+                    t.in_state.as_ref().is_some_and(|s| s.ident == *state) && t.shadows(later)
+                });
+                if let Some(earlier) = earlier {
+                    errors.push(parse::Error::new(
+                        later.event.span(),
+                        format!(
+                            "Transition {} + {} is never taken, an earlier transition of the state handles the same event. Allow shadowed transitions with `#![allow(shadowed_transitions)]`.",
+                            state, later.event
+                        ),
+                    ));
+                    errors.push(parse::Error::new(
+                        earlier.event.span(),
+                        format!(
+                            "Transition {} + {} is handled here first.",
+                            state, later.event
+                        ),
+                    ));
+                }
+            }
+            for (i, later) in sm.wildcards.iter().enumerate() {
+                if let Some(earlier) = sm.wildcards[..i].iter().find(|t| t.shadows(later)) {
+                    errors.push(parse::Error::new(
+                        later.event.span(),
+                        format!(
+                            "Transition _ + {} is never taken, an earlier wildcard transition handles the same event. Allow shadowed transitions with `#![allow(shadowed_transitions)]`.",
+                            later.event
+                        ),
+                    ));
+                    errors.push(parse::Error::new(
+                        earlier.event.span(),
+                        format!("Transition _ + {} is handled here first.", later.event),
+                    ));
+                    continue;
+                }
+
+                // Wildcard transitions are only taken by states which do not handle the event first
+                let shadowing: Option<Vec<&StateTransition>> = states
+                    .keys()
+                    .map(|s| {
+                        states_events_mapping
+                            .get(s)
+                            .into_iter()
+                            .flatten()
+                            .find(|t| t.in_state.is_some() && t.shadows(later))
+                    })
+                    .collect();
+                if let Some(shadowing) = shadowing {
+                    errors.push(parse::Error::new(
+                        later.event.span(),
+                        format!(
+                            "Transition _ + {} is never taken, all states handle the same event first. Allow shadowed transitions with `#![allow(shadowed_transitions)]`.",
+                            later.event
+                        ),
+                    ));
+                    let mut reported: Vec<&StateTransition> = Vec::new();
+                    for transition in shadowing {
+                        if reported.iter().any(|r| std::ptr::eq(*r, transition)) {
+                            continue;
+                        }
+                        reported.push(transition);
+                        errors.push(parse::Error::new(
+                            transition.event.span(),
+                            format!(
+                                "Transition {} + {} is handled here first.",
+                                transition.in_state.as_ref().unwrap().ident,
+                                later.event
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+        let mut errors = errors.into_iter();
+        if let Some(mut error) = errors.next() {
+            error.extend(errors);
+            return Err(error);
+        }
+
+        // Composite states which are resumed remember their last active sub-state, for deep
+        // history so do all the composite states inside of them
+        let mut history_states = IndexSet::new();
+        for transition in sm.transitions.iter().chain(sm.wildcards.iter()) {
+            if let (Some(history), Some(out_state)) =
+                (transition.out_state_history, &transition.out_state)
+            {
+                let s = out_state.to_string();
+                if !initial_states.contains_key(&s) {
+                    return Err(parse::Error::new(
+                        out_state.span(),
+                        format!("State {} has no sub-states to resume with history.", s),
+                    ));
+                }
+                history_states.insert(s.clone());
+
+                if history == History::Deep {
+                    for composite in initial_states.keys() {
+                        let mut state = Some(composite);
+                        while let Some(current) = state {
+                            if *current == s {
+                                history_states.insert(composite.clone());
+                                break;
+                            }
+                            state = parents.get(current);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Extract the starting state, entering its sub-states if it has any
+        let mut starting = start.to_string();
+        while let Some(initial) = initial_states.get(&starting) {
+            starting = initial.clone();
+        }
+        let starting_state = states[&starting].clone();
+
+        Ok(ParsedRegion {
+            name: sm.name,
+            states,
+            start,
+            starting_state,
+            states_events_mapping,
+            transitions: sm.transitions.into_iter().chain(sm.wildcards).collect(),
+            state_definitions,
+            parents,
+            initial_states,
+            history_states,
+        })
+    }
+
+    /// The states which no transitions lead to from the starting state. Composite states are
+    /// listed instead of their sub-states when none of those can be reached.
+    pub fn unreachable_states(&self) -> Vec<String> {
+        // Entering a composite state enters its initial sub-states, and resuming its history
+        // only resumes sub-states which were already reached
+        let mut reached = IndexSet::new();
+        let mut pending = vec![self.starting_state.ident.to_string()];
+        while let Some(state) = pending.pop() {
+            if !reached.insert(state.clone()) {
+                continue;
+            }
+            for transition in self.states_events_mapping.get(&state).into_iter().flatten() {
+                if let Some(out_state) = &transition.out_state {
+                    pending.push(self.initial_leaf(&out_state.to_string()));
+                }
+            }
+        }
+
+        let mut unreachable = Vec::new();
+        for state in self.states.keys().filter(|s| !reached.contains(*s)) {
+            let outermost = self
+                .state_path(state)
+                .into_iter()
+                .find(|s| !reached.iter().any(|r| self.state_path(r).contains(s)))
+                .expect("the state itself is unreachable");
+            if !unreachable.contains(&outermost) {
+                unreachable.push(outermost);
+            }
+        }
+        unreachable
+    }
+
+    /// The span of the declaration of a state, in the states block or its first use in the
+    /// transitions.
+    pub fn declaration_span(&self, state: &str) -> Span {
+        if let Some(def) = self.state_definitions.get(state) {
+            return def.ident.span();
+        }
+        self.transitions
+            .iter()
+            .flat_map(|t| {
+                t.in_state
+                    .iter()
+                    .map(|s| &s.ident)
+                    .chain(t.out_state.iter())
+            })
+            .find(|ident| *ident == state)
+            .map_or(Span::call_site(), |ident| ident.span())
+    }
+
+    /// The entry action of a state, if it has one.
+    pub fn entry_action(&self, state: &str) -> Option<&Expr> {
+        self.state_definitions
+            .get(state)
+            .and_then(|def| def.entry.as_ref())
+    }
+
+    /// The exit action of a state, if it has one.
+    pub fn exit_action(&self, state: &str) -> Option<&Expr> {
+        self.state_definitions
+            .get(state)
+            .and_then(|def| def.exit.as_ref())
+    }
+
+    /// Whether a transition from a state to itself runs its exit and entry actions.
+    pub fn reenters(&self, state: &str) -> bool {
+        self.state_definitions
+            .get(state)
+            .is_none_or(|def| def.reenter)
+    }
+
+    /// Whether a state has sub-states.
+    pub fn is_composite(&self, state: &str) -> bool {
+        self.initial_states.contains_key(state)
+    }
+
+    /// The states enclosing a state, outermost first, followed by the state itself.
+    pub fn state_path(&self, state: &str) -> Vec<String> {
+        let mut path = vec![state.to_string()];
+        while let Some(parent) = self.parents.get(path.last().unwrap()) {
+            path.push(parent.clone());
+        }
+        path.reverse();
+        path
+    }
+
+    /// The state actually entered when transitioning to a state, following the initial
+    /// sub-states of composite states.
+    pub fn initial_leaf(&self, state: &str) -> String {
+        let mut state = state.to_string();
+        while let Some(initial) = self.initial_states.get(&state) {
+            state = initial.clone();
+        }
+        state
+    }
+
+    /// The number of enclosing states which stay active during a transition declared from
+    /// `source` to `target`, the others are exited and entered.
+    pub fn transition_domain(&self, source: &str, target: &str) -> usize {
+        let source_path = self.state_path(source);
+        let target_path = self.state_path(target);
+
+        let common = source_path
+            .iter()
+            .zip(target_path.iter())
+            .take_while(|(s, t)| s == t)
+            .count();
+
+        // When one of the states encloses the other, the outer one is left and entered again
+        // unless it opted out
+        if common == source_path.len().min(target_path.len())
+            && self.reenters(&source_path[common - 1])
+        {
+            common - 1
+        } else {
+            common
+        }
+    }
+}
+
+fn add_state_definition(
+    mut def: StateDefinition,
+    state_definitions: &mut IndexMap<String, StateDefinition>,
+    parents: &mut IndexMap<String, String>,
+    initial_states: &mut IndexMap<String, String>,
+) -> parse::Result<()> {
+    let s = def.ident.to_string();
+    if state_definitions.contains_key(&s) {
+        return Err(parse::Error::new(
+            def.ident.span(),
+            format!("State {} is defined more than once in states.", s),
+        ));
+    }
+
+    let sub_states = std::mem::take(&mut def.states);
+    if !sub_states.is_empty() {
+        if def.fields != syn::Fields::Unit {
+            return Err(parse::Error::new_spanned(
+                &def.fields,
+                format!("State {} has sub-states and cannot have data.", s),
+            ));
+        }
+
+        let mut initial = sub_states.iter().filter(|sub| sub.start);
+        match (initial.next(), initial.next()) {
+            (Some(sub), None) => {
+                initial_states.insert(s.clone(), sub.ident.to_string());
+            }
+            (None, _) => {
+                return Err(parse::Error::new(
+                    def.ident.span(),
+                    format!(
+                        "No initial sub-state defined for {}, indicate it with a *.",
+                        s
+                    ),
+                ))
+            }
+            (Some(_), Some(sub)) => return Err(parse::Error::new(
+                sub.ident.span(),
+                "More than one initial sub-state defined (indicated with *), remove duplicates.",
+            )),
+        }
+    }
+
+    for sub in sub_states.into_iter() {
+        parents.insert(sub.ident.to_string(), s.clone());
+        add_state_definition(sub, state_definitions, parents, initial_states)?;
+    }
+
+    state_definitions.insert(s, def);
+    Ok(())
+}
+
+/// Which of the remembered sub-states a transition to a composite state resumes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum History {
+    /// `(H)`, the direct sub-state, which is entered through its initial sub-states.
+    Shallow,
+    /// `(H*)`, the innermost sub-state.
+    Deep,
+}
+
+#[derive(Debug, Clone)]
+pub struct StateTransition {
+    pub start: bool,
+    pub event: Ident,
+    pub event_pattern: Option<Pat>,
+    pub in_state: Option<Variant>,
+    pub out_state: Option<Ident>,
+    pub out_state_data_expr: Option<Expr>,
+    pub out_state_history: Option<History>,
+    pub guard: Option<Expr>,
+    pub actions: Option<Stmt>,
+}
+
+impl StateTransition {
+    /// Whether this transition takes every event `later` could take, so that `later` is never
+    /// taken when declared after it for the same state.
+    pub fn shadows(&self, later: &StateTransition) -> bool {
+        let tokens = |tokens: &dyn ToTokens| tokens.to_token_stream().to_string();
+
+        let pattern = match (&self.event_pattern, &later.event_pattern) {
+            (None, _) | (Some(Pat::Wild(_)), _) => true,
+            (Some(p), Some(later)) => tokens(p) == tokens(later),
+            (Some(_), None) => false,
+        };
+        let guard = match (&self.guard, &later.guard) {
+            (None, _) => true,
+            (Some(g), Some(later)) => tokens(g) == tokens(later),
+            (Some(_), None) => false,
+        };
+
+        self.event == later.event && pattern && guard
+    }
+
+    /// The event with its pattern, as written in the DSL.
+    pub fn event_text(&self) -> String {
+        match &self.event_pattern {
+            Some(p) => format!("{}({})", self.event, p.to_token_stream()),
+            None => self.event.to_string(),
+        }
+    }
+
+    /// The guard, as written in the DSL.
+    pub fn guard_text(&self) -> Option<String> {
+        self.guard.as_ref().map(|g| g.to_token_stream().to_string())
+    }
+
+    /// The actions, without the `;` ending them.
+    pub fn action_text(&self) -> Option<String> {
+        self.actions.as_ref().map(|actions| match actions {
+            Stmt::Semi(expr, _) => expr.to_token_stream().to_string(),
+            actions => actions.to_token_stream().to_string(),
+        })
+    }
+
+    /// The destination state with its history or data, as written in the DSL.
+    pub fn out_state_text(&self) -> Option<String> {
+        let out_state = self.out_state.as_ref()?;
+        Some(match (&self.out_state_history, &self.out_state_data_expr) {
+            (Some(History::Shallow), _) => format!("{}(H)", out_state),
+            (Some(History::Deep), _) => format!("{}(H*)", out_state),
+            (None, Some(e)) => format!("{}({})", out_state, e.to_token_stream()),
+            (None, None) => out_state.to_string(),
+        })
+    }
+}
+
+impl parse::Parse for StateTransition {
+    fn parse(input: parse::ParseStream) -> syn::Result<Self> {
+        // Check for starting state definition
+        let start = input.parse::<Token![*]>().is_ok();
+
+        // Parse the DSL
+        //
+        // Transition DSL:
+        // SrcStateVariant + Event(OptionalPattern) [ guard ] / { actions } = DstState(OptionalExpr)
+        // _ + Event(OptionalPattern) [ guard ] / { actions } = DstState(OptionalExpr)
+        // SrcStateVariant + Event(OptionalPattern) [ guard ] / { actions } = DstState(H or H*)
+
+        // Input State
+        // Variant or _
+        let in_state: Option<Variant> = if input.peek(Token![_]) {
+            let underscore = input.parse::<Token![_]>()?;
+            if start {
+                return Err(parse::Error::new(
+                    underscore.span,
+                    "The starting state cannot be a wildcard, mark a named state with a * instead.",
+                ));
+            }
+            None
+        } else if input.peek(Ident) || input.peek(Token![#]) {
+            Some(input.parse()?)
+        } else {
+            return Err(
+                input.error("Expected the name of a state, or _ for a transition from any state.")
+            );
+        };
+
+        // Event
+        if !input.peek(Token![+]) {
+            return Err(input.error(
+                "Expected + and an event after the state, as in `State + Event = NextState`.",
+            ));
+        }
+        input.parse::<Token![+]>()?;
+        if !input.peek(Ident) {
+            return Err(input.error("Expected the name of an event after +."));
+        }
+        let event: Ident = input.parse()?;
+
+        //optional pattern
+        let event_pattern: Option<Pat> = if input.peek(token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            Some(content.parse()?)
+        } else {
+            None
+        };
+
+        // Possible guard
+        let guard: Option<Expr> = if input.peek(token::Bracket) {
+            let content;
+            bracketed!(content in input);
+            Some(content.parse()?)
+        } else {
+            None
+        };
+
+        // Possible action
+        let actions: Option<Stmt> = if input.parse::<Token![/]>().is_ok() {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+
+        //possible transition
+        let (out_state, out_state_data_expr, out_state_history) =
+            if input.parse::<Token![=]>().is_ok() {
+                if !input.peek(Ident) {
+                    return Err(input.error("Expected the name of the state to go to after =."));
+                }
+                let out_state: Ident = input.parse()?;
+                let (out_state_data_expr, out_state_history) = if input.peek(token::Paren) {
+                    let content;
+                    parenthesized!(content in input);
+                    if let Some(history) = parse_history(&content)? {
+                        (None, Some(history))
+                    } else {
+                        (Some(content.parse()?), None)
+                    }
+                } else {
+                    (None, None)
+                };
+                (Some(out_state), out_state_data_expr, out_state_history)
+            } else {
+                (None, None, None)
+            };
+
+        Ok(StateTransition {
+            start,
+            in_state,
+            out_state,
+            out_state_data_expr,
+            out_state_history,
+            event,
+            event_pattern,
+            guard,
+            actions,
+        })
+    }
+}
+
+/// The name closest to a misspelled one, if any is close enough to be what was meant.
+fn closest<'a>(name: &Ident, names: impl Iterator<Item = &'a Ident>) -> Option<&'a Ident> {
+    let name = name.to_string();
+    let max_distance = std::cmp::max(name.chars().count() / 3, 1);
+    names
+        .map(|candidate| (edit_distance(&name, &candidate.to_string()), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// The edit distance between two strings, counting insertions, deletions, substitutions and
+/// swaps of adjacent characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Parses a history marker, `H` or `H*`, if that is all there is in the stream.
+fn parse_history(input: parse::ParseStream) -> syn::Result<Option<History>> {
+    let fork = input.fork();
+    match fork.parse::<Ident>() {
+        Ok(ident) if ident == "H" => {}
+        _ => return Ok(None),
+    }
+
+    let history = if fork.peek(Token![*]) {
+        fork.parse::<Token![*]>()?;
+        History::Deep
+    } else {
+        History::Shallow
+    };
+
+    if !fork.is_empty() {
+        return Ok(None);
+    }
+
+    input.advance_to(&fork);
+    Ok(Some(history))
+}
+
+#[derive(Debug)]
+pub struct StateDefinition {
+    pub start: bool,
+    pub ident: Ident,
+    pub fields: syn::Fields,
+    pub entry: Option<Expr>,
+    pub exit: Option<Expr>,
+    pub reenter: bool,
+    pub states: Vec<StateDefinition>,
+}
+
+/// Parses a braced, comma separated list of state definitions.
+fn parse_state_definitions(input: parse::ParseStream) -> syn::Result<Vec<StateDefinition>> {
+    let mut states = Vec::new();
+
+    let content;
+    braced!(content in input);
+    loop {
+        if content.is_empty() {
+            break;
+        }
+
+        states.push(content.parse()?);
+
+        // No comma at end of line, no more states
+        if content.is_empty() {
+            break;
+        }
+
+        if content.parse::<Token![,]>().is_err() {
+            break;
+        };
+    }
+
+    Ok(states)
+}
+
+impl parse::Parse for StateDefinition {
+    fn parse(input: parse::ParseStream) -> syn::Result<Self> {
+        // Parse the DSL
+        //
+        // State DSL:
+        // State(Data) { entry: expr, exit: expr, reenter: bool, states: { *SubState, ... } }
+        //
+        // The data, all the keys, and the braces, are optional. A * marks the initial sub-state.
+        let start = input.parse::<Token![*]>().is_ok();
+        let ident: Ident = input.parse()?;
+        let fields = if input.peek(token::Paren) {
+            syn::Fields::Unnamed(input.parse()?)
+        } else {
+            syn::Fields::Unit
+        };
+
+        let mut state = StateDefinition {
+            start,
+            ident,
+            fields,
+            entry: None,
+            exit: None,
+            reenter: true,
+            states: Vec::new(),
+        };
+
+        if input.peek(token::Brace) {
+            let content;
+            braced!(content in input);
+            loop {
+                if content.is_empty() {
+                    break;
+                }
+
+                let keyword: Ident = content.parse()?;
+                content.parse::<Token![:]>()?;
+                match keyword.to_string().as_str() {
+                    "entry" => state.entry = Some(content.parse()?),
+                    "exit" => state.exit = Some(content.parse()?),
+                    "reenter" => state.reenter = content.parse::<LitBool>()?.value,
+                    "states" => state.states = parse_state_definitions(&content)?,
+                    keyword_str => {
+                        return Err(parse::Error::new(
+                            keyword.span(),
+                            format!(
+                                "Unknown keyword {}. Support keywords: [\"entry\", \"exit\", \"reenter\", \"states\"]",
+                                keyword_str
+                            ),
+                        ))
+                    }
+                }
+
+                // No comma at end of line, no more keys
+                if content.is_empty() {
+                    break;
+                }
+
+                if content.parse::<Token![,]>().is_err() {
+                    break;
+                };
+            }
+        }
+
+        Ok(state)
+    }
+}
+
+/// Parses the keys which can be used both for the whole state machine and for a region, returns
+/// `false` for any other key.
+fn parse_region_key(
+    keyword: &str,
+    input: parse::ParseStream,
+    region: &mut Region,
+) -> parse::Result<bool> {
+    match keyword {
+        "transitions" => {
+            input.parse::<Token![:]>()?;
+            if input.peek(token::Brace) {
+                let content;
+                braced!(content in input);
+                loop {
+                    if content.is_empty() {
+                        break;
+                    }
+
+                    let transition: StateTransition = content.parse()?;
+                    if transition.in_state.is_some() {
+                        region.add_transition(transition);
+                    } else {
+                        region.add_wildcard(transition);
+                    }
+
+                    // No comma at end of line, no more transitions
+                    if content.is_empty() {
+                        break;
+                    }
+
+                    if content.parse::<Token![,]>().is_err() {
+                        break;
+                    };
+                }
+            }
+        }
+        "states" => {
+            input.parse::<Token![:]>()?;
+            for state in parse_state_definitions(input)? {
+                region.add_state_definition(state);
+            }
+        }
+        _ => return Ok(false),
+    }
+
+    Ok(true)
+}
+
+impl parse::Parse for Region {
+    fn parse(input: parse::ParseStream) -> parse::Result<Self> {
+        // Parse the DSL
+        //
+        // Region DSL:
+        // Name { transitions: { ... }, states: { ... } }
+        let mut region = Region::new(Some(input.parse()?));
+
+        let content;
+        braced!(content in input);
+        loop {
+            if content.is_empty() {
+                break;
+            }
+
+            let keyword: Ident = content.parse()?;
+            if !parse_region_key(&keyword.to_string(), &content, &mut region)? {
+                return Err(parse::Error::new(
+                    keyword.span(),
+                    format!(
+                        "Unknown keyword {}. Support keywords: [\"transitions\", \"states\"]",
+                        keyword
+                    ),
+                ));
+            }
+
+            // No comma at end of line, no more keys
+            if content.is_empty() {
+                break;
+            }
+
+            if content.parse::<Token![,]>().is_err() {
+                break;
+            };
+        }
+
+        Ok(region)
+    }
+}
+
+impl parse::Parse for StateMachine {
+    fn parse(input: parse::ParseStream) -> parse::Result<Self> {
+        let mut statemachine = StateMachine::new();
+
+        // Checks are turned off with inner attributes at the start, `#![allow(lint, ...)]`
+        for attr in Attribute::parse_inner(input)? {
+            if !attr.path.is_ident("allow") {
+                return Err(parse::Error::new_spanned(
+                    attr,
+                    "Only `#![allow(...)]` attributes are supported.",
+                ));
+            }
+            let lints = attr.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)?;
+            for lint in lints.into_iter() {
+                if !LINTS.iter().any(|l| lint == l) {
+                    return Err(parse::Error::new(
+                        lint.span(),
+                        format!("Unknown lint {}. Supported lints: {:?}", lint, LINTS),
+                    ));
+                }
+                statemachine.allowed.push(lint);
+            }
+        }
+
+        loop {
+            // If the last line ends with a comma this is true
+            if input.is_empty() {
+                break;
+            }
+
+            let keyword: Ident = input.parse()?;
+            match keyword.to_string().as_str() {
+                "regions" => {
+                    input.parse::<Token![:]>()?;
+                    let content;
+                    braced!(content in input);
+                    loop {
+                        if content.is_empty() {
+                            break;
+                        }
+
+                        statemachine.add_region(content.parse()?);
+
+                        // No comma at end of line, no more regions
+                        if content.is_empty() {
+                            break;
+                        }
+
+                        if content.parse::<Token![,]>().is_err() {
+                            break;
+                        };
+                    }
+                }
+                "states_attr" => {
+                    input.parse::<Token![:]>()?;
+                    statemachine.add_state_attrs(Attribute::parse_outer(input)?);
+                }
+                "events" => {
+                    input.parse::<Token![:]>()?;
+                    let content;
+                    braced!(content in input);
+                    let events = content.parse_terminated::<_, Token![,]>(Variant::parse)?;
+                    statemachine.events = Some(events.into_iter().collect());
+                }
+                "name" => {
+                    input.parse::<Token![:]>()?;
+                    statemachine.name = Some(input.parse()?);
+                }
+                "context_type" => {
+                    input.parse::<Token![:]>()?;
+                    statemachine.context_type = Some(input.parse()?);
+                }
+                "diagram" => {
+                    input.parse::<Token![:]>()?;
+                    statemachine.diagram = Some(input.parse()?);
+                }
+                "events_attr" => {
+                    input.parse::<Token![:]>()?;
+                    statemachine.add_event_attrs(Attribute::parse_outer(input)?);
+                }
+                "guard_error" => {
+                    input.parse::<Token![:]>()?;
+                    statemachine.guard_error = Some(input.parse()?);
+                }
+                "action_error" => {
+                    input.parse::<Token![:]>()?;
+                    statemachine.action_error = Some(input.parse()?);
+                }
+                "temporary_context" => {
+                    input.parse::<Token![:]>()?;
+                    statemachine.temporary_context = Some(input.parse()?);
+                }
+                key => {
+                    if !parse_region_key(key, input, &mut statemachine.region)? {
+                        return Err(parse::Error::new(
+                            keyword.span(),
+                            format!(
+                            "Unknown keyword {}. Support keywords: [\"transitions\", \"states\", \"regions\", \"states_attr\", \"events\", \"events_attr\", \"guard_error\", \"action_error\", \"temporary_context\", \"name\", \"context_type\", \"diagram\"]",
+                            keyword
+                        ),
+                        ));
+                    }
+                }
+            }
+
+            // No comma at end of line, no more transitions
+            if input.is_empty() {
+                break;
+            }
+
+            if input.parse::<Token![,]>().is_err() {
+                break;
+            };
+        }
+
+        Ok(statemachine)
+    }
+}
@@ -0,0 +1,180 @@
+use crate::diagram::{container, transition_label, RegionDiagram};
+use crate::parser::*;
+use quote::quote;
+
+/// The declaration of a state, naming it with its prefix if it has one.
+fn declaration(diagram: &RegionDiagram, state: &str) -> String {
+    if diagram.prefix.is_empty() {
+        format!("state {}", state)
+    } else {
+        format!("state \"{}\" as {}", state, diagram.name(state))
+    }
+}
+
+/// Writes the states and transitions inside of `parent`, or at the top of the region for
+/// `None`.
+fn write(diagram: &RegionDiagram, out: &mut Vec<String>, parent: Option<&String>, indent: &str) {
+    let initial = match parent {
+        Some(parent) => diagram.region.initial_states[parent].clone(),
+        None => diagram.region.start.to_string(),
+    };
+    out.push(format!("{}[*] --> {}", indent, diagram.name(&initial)));
+
+    for state in diagram
+        .states
+        .iter()
+        .filter(|s| diagram.region.parents.get(*s) == parent)
+    {
+        if diagram.region.is_composite(state) {
+            out.push(format!("{}{} {{", indent, declaration(diagram, state)));
+            write(diagram, out, Some(state), &format!("{}    ", indent));
+            out.push(format!("{}}}", indent));
+        } else {
+            out.push(format!("{}{}", indent, declaration(diagram, state)));
+
+            // The data of a state is shown in a note next to it
+            let types: Vec<String> = diagram.region.states[state]
+                .fields
+                .iter()
+                .map(|field| {
+                    let ty = &field.ty;
+                    quote! { #ty }.to_string()
+                })
+                .collect();
+            if !types.is_empty() {
+                out.push(format!(
+                    "{}note right of {} : {}",
+                    indent,
+                    diagram.name(state),
+                    types.join(", ")
+                ));
+            }
+        }
+    }
+
+    if parent.is_none()
+        && diagram
+            .region
+            .transitions
+            .iter()
+            .any(|t| t.in_state.is_none())
+    {
+        out.push(format!(
+            "{}state \"_\" as {} ##[dashed]",
+            indent,
+            diagram.name("any")
+        ));
+    }
+
+    for transition in diagram
+        .region
+        .transitions
+        .iter()
+        .filter(|t| container(diagram.region, t).as_ref() == parent)
+    {
+        let source = match &transition.in_state {
+            Some(in_state) => diagram.name(&in_state.ident.to_string()),
+            None => diagram.name("any"),
+        };
+        let label = transition_label(transition);
+        match &transition.out_state {
+            Some(out_state) => out.push(format!(
+                "{}{} --> {} : {}",
+                indent,
+                source,
+                diagram.name(&out_state.to_string()),
+                label
+            )),
+            // Internal transitions are listed in the state they happen in
+            None => out.push(format!("{}{} : {}", indent, source, label)),
+        }
+    }
+}
+
+/// Generates a string containing PlantUML syntax for a statemachine state diagram.
+///
+/// Wildcard transitions are drawn once from a dashed `_` state, internal transitions are listed
+/// inside of their state, the data of states is shown in notes, and regions are drawn as
+/// composite states.
+pub fn generate_diagram(sm: &ParsedStateMachine) -> String {
+    let mut lines = vec!["@startuml".to_string()];
+
+    for region in sm.regions.iter() {
+        let diagram = RegionDiagram::new(
+            region,
+            region
+                .name
+                .as_ref()
+                .map_or(String::new(), |name| format!("{}_", name)),
+        );
+
+        match &region.name {
+            Some(name) => {
+                lines.push(format!("state {} {{", name));
+                write(&diagram, &mut lines, None, "    ");
+                lines.push("}".to_string());
+            }
+            None => write(&diagram, &mut lines, None, ""),
+        }
+    }
+
+    lines.push("@enduml".to_string());
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagram::fixtures;
+
+    #[test]
+    fn transitions() {
+        let plantuml = generate_diagram(&fixtures::transitions());
+
+        assert_eq!(
+            plantuml,
+            r#"@startuml
+[*] --> State1
+state State1
+state State2
+note right of State2 : u32
+state "_" as any ##[dashed]
+State1 --> State2 : Event1(MyEventData { x : 1 , .. }) [guard] / action ()
+State2 --> State1 : Event2 [* state > 0 && ready ()] / { a () ; b () }
+State2 : Event3 / count ()
+any --> State1 : Reset
+@enduml
+"#
+        );
+    }
+
+    #[test]
+    fn composite_states_and_regions() {
+        let plantuml = generate_diagram(&fixtures::composite_states_and_regions());
+
+        assert_eq!(
+            plantuml,
+            r#"@startuml
+state Main {
+    [*] --> Main_Operational
+    state "Operational" as Main_Operational {
+        [*] --> Main_Idle
+        state "Idle" as Main_Idle
+        state "Busy" as Main_Busy
+        Main_Idle --> Main_Busy : Start
+    }
+    state "Error" as Main_Error
+    Main_Operational --> Main_Error : Fault
+    Main_Error --> Main_Operational : Reset (H)
+}
+state Power {
+    [*] --> Power_On
+    state "On" as Power_On
+    state "Off" as Power_Off
+    Power_On --> Power_Off : Off
+}
+@enduml
+"#
+        );
+    }
+}
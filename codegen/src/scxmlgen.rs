@@ -0,0 +1,246 @@
+use crate::diagram::RegionDiagram;
+use crate::parser::*;
+use quote::quote;
+
+/// Escapes text for use in an XML attribute.
+fn escape(v: String) -> String {
+    v.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The `cond` of a transition, matching its event pattern unless it is `_` and checking its guard.
+fn condition(transition: &StateTransition) -> Option<String> {
+    let pattern = match &transition.event_pattern {
+        Some(syn::Pat::Wild(_)) | None => None,
+        Some(p) => Some(format!("matches!(event, {})", quote! {#p})),
+    };
+    let guard = transition.guard.as_ref().map(|g| quote! {#g}.to_string());
+
+    match (pattern, guard) {
+        (Some(pattern), Some(guard)) => Some(format!("{} && ({})", pattern, guard)),
+        (pattern, guard) => pattern.or(guard),
+    }
+}
+
+/// The id of the pseudo-state resuming the history of `composite`.
+fn history_id(diagram: &RegionDiagram, composite: &str, history: History) -> String {
+    match history {
+        History::Shallow => diagram.name(&format!("{}_history", composite)),
+        History::Deep => diagram.name(&format!("{}_deep_history", composite)),
+    }
+}
+
+/// The id of the state a region starts in.
+fn start(diagram: &RegionDiagram) -> String {
+    diagram.name(&diagram.region.start.to_string())
+}
+
+fn write_transition(
+    diagram: &RegionDiagram,
+    out: &mut Vec<String>,
+    transition: &StateTransition,
+    indent: &str,
+) {
+    let mut attributes = vec![format!("event=\"{}\"", transition.event)];
+    if let Some(cond) = condition(transition) {
+        attributes.push(format!("cond=\"{}\"", escape(cond)));
+    }
+    if let Some(out_state) = &transition.out_state {
+        let target = match transition.out_state_history {
+            Some(history) => history_id(diagram, &out_state.to_string(), history),
+            None => diagram.name(&out_state.to_string()),
+        };
+        attributes.push(format!("target=\"{}\"", target));
+    }
+    out.push(format!("{}<transition {}/>", indent, attributes.join(" ")));
+}
+
+/// Writes the states inside of `parent`, or at the top of the region for `None`.
+fn write_states(
+    diagram: &RegionDiagram,
+    out: &mut Vec<String>,
+    parent: Option<&String>,
+    indent: &str,
+) {
+    for state in diagram
+        .states
+        .iter()
+        .filter(|s| diagram.region.parents.get(*s) == parent)
+    {
+        let inner = format!("{}    ", indent);
+        let mut children = Vec::new();
+
+        if diagram.region.is_composite(state) {
+            // A history pseudo-state is only written for the kinds of history used
+            for history in [History::Shallow, History::Deep] {
+                let used = diagram.region.transitions.iter().any(|t| {
+                    t.out_state_history == Some(history)
+                        && t.out_state.as_ref().is_some_and(|s| s == state)
+                });
+                if used {
+                    let kind = match history {
+                        History::Shallow => "shallow",
+                        History::Deep => "deep",
+                    };
+                    children.push(format!(
+                        "{}<history id=\"{}\" type=\"{}\">",
+                        inner,
+                        history_id(diagram, state, history),
+                        kind
+                    ));
+                    children.push(format!(
+                        "{}    <transition target=\"{}\"/>",
+                        inner,
+                        diagram.name(&diagram.region.initial_states[state])
+                    ));
+                    children.push(format!("{}</history>", inner));
+                }
+            }
+            write_states(diagram, &mut children, Some(state), &inner);
+        }
+
+        for transition in diagram.region.transitions.iter().filter(|t| {
+            t.in_state
+                .as_ref()
+                .is_some_and(|s| s.ident == state.as_str())
+        }) {
+            write_transition(diagram, &mut children, transition, &inner);
+        }
+
+        // Wildcard transitions apply to all states, so they are repeated in each of the
+        // outermost states, after the transitions taking precedence over them
+        if parent.is_none() {
+            for transition in diagram
+                .region
+                .transitions
+                .iter()
+                .filter(|t| t.in_state.is_none())
+            {
+                write_transition(diagram, &mut children, transition, &inner);
+            }
+        }
+
+        let initial = match diagram.region.initial_states.get(state) {
+            Some(initial) => format!(" initial=\"{}\"", diagram.name(initial)),
+            None => String::new(),
+        };
+        if children.is_empty() {
+            out.push(format!("{}<state id=\"{}\"/>", indent, diagram.name(state)));
+        } else {
+            out.push(format!(
+                "{}<state id=\"{}\"{}>",
+                indent,
+                diagram.name(state),
+                initial
+            ));
+            out.extend(children);
+            out.push(format!("{}</state>", indent));
+        }
+    }
+}
+
+/// Generates a string containing a W3C SCXML document of a statemachine.
+///
+/// Guards and event patterns become the `cond` of transitions, wildcard transitions are repeated
+/// in every outermost state and regions are the children of a `parallel` state. Actions are not
+/// exported.
+pub fn generate_diagram(sm: &ParsedStateMachine) -> String {
+    let mut lines = Vec::new();
+
+    let initial = if sm.has_regions() {
+        lines.push("    <parallel id=\"Regions\">".to_string());
+        for region in sm.regions.iter() {
+            let name = region.name.as_ref().expect("regions have names");
+            let document = RegionDiagram::new(region, format!("{}.", name));
+            lines.push(format!(
+                "        <state id=\"{}\" initial=\"{}\">",
+                name,
+                start(&document)
+            ));
+            write_states(&document, &mut lines, None, "            ");
+            lines.push("        </state>".to_string());
+        }
+        lines.push("    </parallel>".to_string());
+        "Regions".to_string()
+    } else {
+        let document = RegionDiagram::new(&sm.regions[0], String::new());
+        write_states(&document, &mut lines, None, "    ");
+        start(&document)
+    };
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<scxml xmlns=\"http://www.w3.org/2005/07/scxml\" version=\"1.0\" initial=\"{}\">
+{}
+</scxml>
+",
+        initial,
+        lines.join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagram::fixtures;
+
+    #[test]
+    fn transitions() {
+        let scxml = generate_diagram(&fixtures::transitions());
+
+        assert_eq!(
+            scxml,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<scxml xmlns="http://www.w3.org/2005/07/scxml" version="1.0" initial="State1">
+    <state id="State1">
+        <transition event="Event1" cond="matches!(event, MyEventData { x : 1 , .. }) &amp;&amp; (guard)" target="State2"/>
+        <transition event="Reset" target="State1"/>
+    </state>
+    <state id="State2">
+        <transition event="Event2" cond="* state &gt; 0 &amp;&amp; ready ()" target="State1"/>
+        <transition event="Event3"/>
+        <transition event="Reset" target="State1"/>
+    </state>
+</scxml>
+"#
+        );
+    }
+
+    #[test]
+    fn composite_states_and_regions() {
+        let scxml = generate_diagram(&fixtures::composite_states_and_regions());
+
+        assert_eq!(
+            scxml,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<scxml xmlns="http://www.w3.org/2005/07/scxml" version="1.0" initial="Regions">
+    <parallel id="Regions">
+        <state id="Main" initial="Main.Operational">
+            <state id="Main.Operational" initial="Main.Idle">
+                <history id="Main.Operational_history" type="shallow">
+                    <transition target="Main.Idle"/>
+                </history>
+                <state id="Main.Idle">
+                    <transition event="Start" target="Main.Busy"/>
+                </state>
+                <state id="Main.Busy"/>
+                <transition event="Fault" target="Main.Error"/>
+            </state>
+            <state id="Main.Error">
+                <transition event="Reset" target="Main.Operational_history"/>
+            </state>
+        </state>
+        <state id="Power" initial="Power.On">
+            <state id="Power.On">
+                <transition event="Off" target="Power.Off"/>
+            </state>
+            <state id="Power.Off"/>
+        </state>
+    </parallel>
+</scxml>
+"#
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! Action error example
+//!
+//! An example of actions which can fail, aborting the transition and leaving the state machine in
+//! the state it was in.
+
+#![deny(missing_docs)]
+
+use smlang::{statemachine, Handled};
+
+///Events
+pub enum Events {
+    ///Power on the radio
+    PowerOn,
+    ///Send a message
+    Send(u8),
+    ///Power off the radio
+    PowerOff,
+}
+
+/// Errors from writing to the radio
+#[derive(Debug, PartialEq)]
+pub enum BusError {
+    /// The radio did not acknowledge the write
+    Nack,
+}
+
+statemachine! {
+    action_error: BusError,
+    states: {
+        Off {
+            exit: ctx.log.push(0x0f),
+        },
+    },
+    transitions: {
+        *Off + PowerOn / ctx.write(0x01); = Idle,
+        Idle + Send(_) / ctx.write(*event);,
+        Idle + PowerOff / { ctx.log.push(0xff) }; = Off,
+    }
+}
+
+/// Context
+#[derive(Default)]
+pub struct Context {
+    connected: bool,
+    log: Vec<u8>,
+}
+
+impl Context {
+    fn write(&mut self, value: u8) -> Result<(), BusError> {
+        if self.connected {
+            self.log.push(value);
+            Ok(())
+        } else {
+            Err(BusError::Nack)
+        }
+    }
+}
+
+fn main() {
+    let mut sm = StateMachine::new(Context::default());
+
+    // A failed action aborts the transition
+    let r = sm.process_event(Events::PowerOn);
+    assert!(r == Err(Error::ActionError(BusError::Nack)));
+    assert!(sm.state() == &States::Off);
+
+    // The action runs before the exit action, which is only run once the transition succeeded
+    assert!(sm.context().log.is_empty());
+    sm.context_mut().connected = true;
+    let r = sm.process_event(Events::PowerOn);
+    assert!(r == Ok(Handled::Transition(&States::Idle)));
+    assert_eq!(sm.context().log, [0x01, 0x0f]);
+
+    // Internal transitions report their failures too
+    let r = sm.process_event(Events::Send(42));
+    assert!(r == Ok(Handled::Internal(&States::Idle)));
+    sm.context_mut().connected = false;
+    let r = sm.process_event(Events::Send(43));
+    assert!(r == Err(Error::ActionError(BusError::Nack)));
+
+    // Actions which cannot fail keep working alongside them
+    let r = sm.process_event(Events::PowerOff);
+    assert!(r == Ok(Handled::Transition(&States::Off)));
+    assert_eq!(sm.context().log, [0x01, 0x0f, 42, 0xff]);
+}
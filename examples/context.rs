@@ -40,9 +40,9 @@ impl Context {
 fn main() {
     let mut sm = StateMachine::new(Context { num_transitions: 0 });
 
-    assert!(sm.process_event(Events::Event1).is_some()); // ++
-    assert!(sm.process_event(Events::Event1).is_none()); // Will fail
-    assert!(sm.process_event(Events::Event2).is_some()); // ++
+    assert!(sm.process_event(Events::Event1).is_ok()); // ++
+    assert!(sm.process_event(Events::Event1) == Err(Error::InvalidEvent)); // Will fail
+    assert!(sm.process_event(Events::Event2).is_ok()); // ++
 
     assert_eq!(sm.context().num_transitions, 2);
 
@@ -0,0 +1,35 @@
+//! Diagram example
+//!
+//! An example of writing the diagram of a state machine to a file, and of using it from the
+//! generated `STATEMACHINE_DOT` constant. Run with `--features graphviz`.
+
+#![deny(missing_docs)]
+
+use smlang::statemachine;
+
+statemachine! {
+    name: Door,
+    events: { Open, Close, Lock, Unlock },
+    diagram: "target/diagrams/door.dot",
+    transitions: {
+        *Closed + Open = Opened,
+        Opened + Close = Closed,
+        Closed + Lock = Locked,
+        Locked + Unlock = Closed,
+    }
+}
+
+/// Context
+pub struct DoorContext;
+
+fn main() {
+    let dot = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/target/diagrams/door.dot"
+    ))
+    .unwrap();
+    assert_eq!(dot, DOOR_STATEMACHINE_DOT);
+    assert!(dot.contains("\"Closed\" -> \"Locked\""));
+
+    print!("{}", DOOR_STATEMACHINE_DOT);
+}
@@ -0,0 +1,35 @@
+//! Mermaid diagram example
+//!
+//! An example of writing the Mermaid diagram of a state machine to a file, for embedding in
+//! Markdown documentation. Run with `--features mermaid`.
+
+#![deny(missing_docs)]
+
+use smlang::statemachine;
+
+statemachine! {
+    name: Door,
+    events: { Open, Close, Lock, Unlock },
+    diagram: "target/diagrams/door.mmd",
+    transitions: {
+        *Closed + Open = Opened,
+        Opened + Close = Closed,
+        Closed + Lock = Locked,
+        Locked + Unlock = Closed,
+    }
+}
+
+/// Context
+pub struct DoorContext;
+
+fn main() {
+    let mermaid = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/target/diagrams/door.mmd"
+    ))
+    .unwrap();
+    assert_eq!(mermaid, DOOR_STATEMACHINE_MERMAID);
+    assert!(mermaid.contains("Closed --> Locked : Lock"));
+
+    print!("{}", DOOR_STATEMACHINE_MERMAID);
+}
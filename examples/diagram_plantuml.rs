@@ -0,0 +1,35 @@
+//! PlantUML diagram example
+//!
+//! An example of writing the PlantUML diagram of a state machine to a file, for including in
+//! architecture documents. Run with `--features plantuml`.
+
+#![deny(missing_docs)]
+
+use smlang::statemachine;
+
+statemachine! {
+    name: Door,
+    events: { Open, Close, Lock, Unlock },
+    diagram: "target/diagrams/door.puml",
+    transitions: {
+        *Closed + Open = Opened,
+        Opened + Close = Closed,
+        Closed + Lock = Locked,
+        Locked + Unlock = Closed,
+    }
+}
+
+/// Context
+pub struct DoorContext;
+
+fn main() {
+    let plantuml = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/target/diagrams/door.puml"
+    ))
+    .unwrap();
+    assert_eq!(plantuml, DOOR_STATEMACHINE_PLANTUML);
+    assert!(plantuml.contains("Closed --> Locked : Lock"));
+
+    print!("{}", DOOR_STATEMACHINE_PLANTUML);
+}
@@ -0,0 +1,82 @@
+//! Entry and exit actions example
+//!
+//! An example of running actions whenever a state is entered or exited, instead of repeating the
+//! same action on every transition.
+
+#![deny(missing_docs)]
+
+use smlang::{statemachine, Handled};
+
+///Events
+pub enum Events {
+    ///Start the motor
+    Start,
+    ///Stop the motor
+    Stop,
+    ///Change speed while running
+    Speed,
+    ///Reset the error counter
+    Reset,
+}
+
+statemachine! {
+    states: {
+        Running {
+            entry: ctx.log("enter Running"),
+            exit: ctx.log("exit Running"),
+        },
+        Stopped {
+            entry: ctx.log("enter Stopped"),
+            exit: ctx.log("exit Stopped"),
+            reenter: false,
+        },
+    },
+    transitions: {
+        *Stopped + Start / ctx.log("start"); = Running,
+        Running + Speed / ctx.log("speed"); = Running,
+        Running + Stop / ctx.log("stop"); = Stopped,
+        Stopped + Reset / ctx.log("reset"); = Stopped,
+        Stopped + Speed / ctx.log("ignored");,
+    }
+}
+
+/// Context
+#[derive(Default)]
+pub struct Context {
+    log: Vec<&'static str>,
+}
+
+impl Context {
+    fn log(&mut self, msg: &'static str) {
+        self.log.push(msg);
+    }
+}
+
+fn main() {
+    let mut sm = StateMachine::new(Context::default());
+
+    // Exit, action and entry, in that order
+    sm.process_event(Events::Start).unwrap();
+    assert_eq!(sm.context().log, ["exit Stopped", "start", "enter Running"]);
+    sm.context_mut().log.clear();
+
+    // Self transitions re-run the exit and entry actions by default
+    sm.process_event(Events::Speed).unwrap();
+    assert_eq!(sm.context().log, ["exit Running", "speed", "enter Running"]);
+    sm.context_mut().log.clear();
+
+    sm.process_event(Events::Stop).unwrap();
+    assert_eq!(sm.context().log, ["exit Running", "stop", "enter Stopped"]);
+    sm.context_mut().log.clear();
+
+    // ... unless the state opts out with `reenter: false`
+    sm.process_event(Events::Reset).unwrap();
+    assert_eq!(sm.context().log, ["reset"]);
+    sm.context_mut().log.clear();
+
+    // Transitions without a destination state never leave the state
+    let r = sm.process_event(Events::Speed);
+    assert!(r == Ok(Handled::Internal(&States::Stopped)));
+    assert_eq!(sm.context().log, ["ignored"]);
+    assert!(sm.state() == &States::Stopped);
+}
@@ -1,6 +1,6 @@
 //! Event pattern example
 
-use smlang::statemachine;
+use smlang::{statemachine, Handled};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct NoteEventData {
@@ -33,6 +33,7 @@ statemachine! {
         *State1 + ButtonEvent(Button { down: true, .. }) / ctx.action(event); = State2,
         State1 + ButtonEvent(_) [!event.down] / {ctx.action(event)} = State3(2),
         State1 + FooEvent("blah") = State3(30),
+        State1 + NoteEvent(_) = State5(event.clone()),
         State3(usize) + ButtonEvent(Button { down: true, ..}) [event.index >= 20 && *state < 20]
             / { ctx.action(event); println!("foo {}", state) } = State3(ctx.action2(*state, event)),
         State3(usize) + ButtonEvent(Button { down: false, ..}) = State3(*state + 1),
@@ -63,32 +64,45 @@ fn main() {
         down: false,
     }));
 
-    assert_eq!(Some(&States::State3(2)), result);
+    assert_eq!(Ok(Handled::Transition(&States::State3(2))), result);
 
-    let result = sm.process_event(Events::FooEvent(&"blah"));
-    assert_eq!(None, result);
+    let result = sm.process_event(Events::FooEvent("blah"));
+    assert_eq!(Err(Error::GuardFailed), result);
 
     let result = sm.process_event(Events::ButtonEvent(Button {
         index: 0,
         down: false,
     }));
-    assert_eq!(Some(&States::State3(3)), result);
+    assert_eq!(Ok(Handled::Transition(&States::State3(3))), result);
 
     let result = sm.process_event(Events::ButtonEvent(Button {
         index: 0,
         down: false,
     }));
-    assert_eq!(Some(&States::State3(4)), result);
+    assert_eq!(Ok(Handled::Transition(&States::State3(4))), result);
 
     let result = sm.process_event(Events::ButtonEvent(Button {
         index: 0,
         down: true,
     }));
-    assert_eq!(None, result);
+    assert_eq!(Err(Error::GuardFailed), result);
 
     let result = sm.process_event(Events::ButtonEvent(Button {
         index: 20,
         down: true,
     }));
-    assert_eq!(Some(&States::State3(2084)), result);
+    assert_eq!(Ok(Handled::Transition(&States::State3(2084))), result);
+
+    let mut sm = StateMachine::new(Context);
+    let result = sm.process_event(Events::NoteEvent(NoteEventData { num: 0, vel: 100 }));
+    assert_eq!(
+        Ok(Handled::Transition(&States::State5(NoteEventData {
+            num: 0,
+            vel: 100
+        }))),
+        result
+    );
+
+    let result = sm.process_event(Events::FooEvent("blah"));
+    assert_eq!(Ok(Handled::Transition(&States::State1)), result);
 }
@@ -4,7 +4,7 @@
 
 #![deny(missing_docs)]
 
-use smlang::statemachine;
+use smlang::{statemachine, Handled};
 
 /// Event data
 #[derive(PartialEq)]
@@ -37,9 +37,9 @@ fn main() {
     let mut sm = StateMachine::new(Context);
     let result = sm.process_event(Events::Event1(MyEventData(1))); // Guard will fail
 
-    assert!(result == None);
+    assert!(result == Err(Error::GuardFailed));
 
     let result = sm.process_event(Events::Event1(MyEventData(42))); // Guard will pass
 
-    assert!(result == Some(&States::State2));
+    assert!(result == Ok(Handled::Transition(&States::State2)));
 }
@@ -4,7 +4,7 @@
 
 #![deny(missing_docs)]
 
-use smlang::statemachine;
+use smlang::{statemachine, Handled};
 
 /// Event data
 #[derive(PartialEq)]
@@ -42,5 +42,5 @@ fn main() {
 
     let result = sm.process_event(Events::Event1(&mut MyEventData(42))); // Guard will pass
 
-    assert!(result == Some(&States::State2));
+    assert!(result == Ok(Handled::Transition(&States::State2)));
 }
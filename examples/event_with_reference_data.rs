@@ -2,7 +2,7 @@
 //!
 //! A simple example of a state machine which will get events that contain references.
 
-use smlang::statemachine;
+use smlang::{statemachine, Handled};
 
 /// Reference wrapper
 #[derive(PartialEq, Debug)]
@@ -46,15 +46,15 @@ fn main() {
     let mut sm = StateMachine::new(Context);
 
     let result = sm.process_event(Events::Event1(&[])); // Guard will fail
-    assert!(result == None);
+    assert!(result == Err(Error::GuardFailed));
     let result = sm.process_event(Events::Event1(&[1, 2, 3])); // Guard will pass
-    assert!(result == Some(&States::State2));
+    assert!(result == Ok(Handled::Transition(&States::State2)));
 
     let r = 42;
     let result = sm.process_event(Events::Event2(MyReferenceWrapper(&r))); // Guard will fail
-    assert!(result == None);
+    assert!(result == Err(Error::GuardFailed));
 
     let r = 9001;
     let result = sm.process_event(Events::Event2(MyReferenceWrapper(&r))); // Guard will pass
-    assert!(result == Some(&States::State3));
+    assert!(result == Ok(Handled::Transition(&States::State3)));
 }
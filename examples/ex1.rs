@@ -5,7 +5,7 @@
 
 #![deny(missing_docs)]
 
-use smlang::statemachine;
+use smlang::{statemachine, Handled};
 
 ///Events
 pub enum Events {
@@ -30,17 +30,17 @@ fn main() {
     assert!(sm.state() == &States::State1);
 
     let r = sm.process_event(Events::Event1);
-    assert!(r == Some(&States::State2));
+    assert!(r == Ok(Handled::Transition(&States::State2)));
 
     let r = sm.process_event(Events::Event2);
-    assert!(r == Some(&States::State3));
+    assert!(r == Ok(Handled::Transition(&States::State3)));
 
     // Now all events will not give any change of state
     let r = sm.process_event(Events::Event1);
-    assert!(r == None);
+    assert!(r == Err(Error::InvalidEvent));
     assert!(sm.state() == &States::State3);
 
     let r = sm.process_event(Events::Event2);
-    assert!(r == None);
+    assert!(r == Err(Error::InvalidEvent));
     assert!(sm.state() == &States::State3);
 }
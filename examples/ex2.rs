@@ -5,7 +5,7 @@
 
 #![deny(missing_docs)]
 
-use smlang::statemachine;
+use smlang::{statemachine, Handled};
 
 ///Events
 pub enum Events {
@@ -33,23 +33,23 @@ fn main() {
     assert!(sm.state() == &States::State1);
 
     let r = sm.process_event(Events::Event1);
-    assert!(r == Some(&States::State2));
+    assert!(r == Ok(Handled::Transition(&States::State2)));
 
     let r = sm.process_event(Events::Event2);
-    assert!(r == Some(&States::State3));
+    assert!(r == Ok(Handled::Transition(&States::State3)));
 
     // Go back in the loop a few time
     let r = sm.process_event(Events::Event3);
-    assert!(r == Some(&States::State2));
+    assert!(r == Ok(Handled::Transition(&States::State2)));
 
     let r = sm.process_event(Events::Event2);
-    assert!(r == Some(&States::State3));
+    assert!(r == Ok(Handled::Transition(&States::State3)));
 
     let r = sm.process_event(Events::Event3);
-    assert!(r == Some(&States::State2));
+    assert!(r == Ok(Handled::Transition(&States::State2)));
 
     // Now we cannot use Event1 again, as it is outside the state machine loop
     let r = sm.process_event(Events::Event1);
-    assert!(r == None);
+    assert!(r == Err(Error::InvalidEvent));
     assert!(sm.state() == &States::State2);
 }
@@ -5,7 +5,7 @@
 
 #![deny(missing_docs)]
 
-use smlang::statemachine;
+use smlang::{statemachine, Handled};
 
 ///Events
 pub enum Events {
@@ -68,7 +68,7 @@ fn main() {
 
         // Go through the first guard and action
         let r = sm.process_event(Events::Event1).await;
-        assert!(r == Some(&States::State2));
+        assert!(r == Ok(Handled::Transition(&States::State2)));
 
         println!("After action 1");
 
@@ -76,7 +76,7 @@ fn main() {
 
         // The action will never run as the guard will fail
         let r = sm.process_event(Events::Event2).await;
-        assert!(r.is_none());
+        assert!(r == Err(Error::GuardFailed));
 
         println!("After action 2");
 
@@ -0,0 +1,58 @@
+//! Generated events example
+//!
+//! An example of declaring the events in the state machine, which then generates the `Events`
+//! enum, instead of writing it by hand.
+
+#![deny(missing_docs)]
+
+use smlang::{statemachine, Handled};
+
+/// Reference wrapper
+#[derive(PartialEq, Debug)]
+pub struct MyReferenceWrapper<'a>(pub &'a u32);
+
+statemachine! {
+    events: {
+        /// Start listening
+        Start,
+        /// Some bytes were received
+        Data(&'a [u8]),
+        /// A value was measured
+        Measurement(MyReferenceWrapper<'b>),
+        /// Stop listening
+        Stop,
+    },
+    events_attr: #[derive(Debug)],
+    transitions: {
+        *Idle + Start = Listening,
+        Listening + Data(_) [!event.is_empty()] / ctx.received += event.len(); = Listening,
+        Listening + Measurement(_) / ctx.last = *event.0;,
+        Listening + Stop = Idle,
+    }
+}
+
+/// Context
+#[derive(Default)]
+pub struct Context {
+    received: usize,
+    last: u32,
+}
+
+fn main() {
+    let mut sm = StateMachine::new(Context::default());
+
+    sm.process_event(Events::Start).unwrap();
+    sm.process_event(Events::Data(&[1, 2, 3])).unwrap();
+    let r = sm.process_event(Events::Data(&[]));
+    assert!(r == Err(Error::GuardFailed));
+
+    let value = 42;
+    let r = sm.process_event(Events::Measurement(MyReferenceWrapper(&value)));
+    assert!(r == Ok(Handled::Internal(&States::Listening)));
+
+    assert_eq!(format!("{:?}", Events::Stop), "Stop");
+    sm.process_event(Events::Stop).unwrap();
+    assert!(sm.state() == &States::Idle);
+    assert_eq!(sm.context().received, 3);
+    assert_eq!(sm.context().last, 42);
+}
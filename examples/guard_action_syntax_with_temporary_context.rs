@@ -1,11 +1,11 @@
 //! Guard and action syntax example
 //!
-//! An example of using guards and actions with state and event data.
+//! An example of using guards and actions with state and event data, and a temporary context
+//! which is only borrowed for the duration of each call to `process_event`.
 
-/*
 #![deny(missing_docs)]
 
-use smlang::statemachine;
+use smlang::{statemachine, Handled};
 
 /// Event data
 #[derive(PartialEq)]
@@ -15,11 +15,22 @@ pub struct MyEventData(pub u32);
 #[derive(PartialEq)]
 pub struct MyStateData(pub u32);
 
+#[derive(PartialEq)]
+///Events
+pub enum Events {
+    ///Event1 with data
+    Event1(MyEventData),
+    ///Event2
+    Event2,
+}
+
 statemachine! {
     temporary_context: &mut u16,
     transitions: {
-        *State1 + Event1(MyEventData) [guard1] / action1 = State2,
-        State2(MyStateData) + Event2  [guard2] / action2 = State3,
+        *State1 + Event1(_) [ctx.guard1(temporary_context, event)]
+            / ctx.action1(temporary_context, event); = State2(MyStateData(*temporary_context as u32)),
+        State2(MyStateData) + Event2 [ctx.guard2(temporary_context, state)]
+            / ctx.action2(temporary_context, state); = State3,
         // ...
     },
 }
@@ -27,26 +38,24 @@ statemachine! {
 /// Context
 pub struct Context;
 
-impl StateMachineContext for Context {
+impl Context {
     // Guard1 has access to the data from Event1
-    fn guard1(&mut self, temp_context: &mut u16, _event_data: &MyEventData) -> Result<(), ()> {
+    fn guard1(&mut self, temp_context: &mut u16, _event_data: &MyEventData) -> bool {
         *temp_context += 1;
 
-        Ok(())
+        true
     }
 
-    // Action1 has access to the data from Event1, and need to return the state data for State2
-    fn action1(&mut self, temp_context: &mut u16, _event_data: &MyEventData) -> MyStateData {
+    // Action1 has access to the data from Event1
+    fn action1(&mut self, temp_context: &mut u16, _event_data: &MyEventData) {
         *temp_context += 1;
-
-        MyStateData(1)
     }
 
     // Guard2 has access to the data from State2
-    fn guard2(&mut self, temp_context: &mut u16, _state_data: &MyStateData) -> Result<(), ()> {
+    fn guard2(&mut self, temp_context: &mut u16, _state_data: &MyStateData) -> bool {
         *temp_context += 1;
 
-        Ok(())
+        true
     }
 
     // Action2 has access to the data from State2
@@ -64,11 +73,13 @@ fn main() {
         .process_event(&mut val, Events::Event1(MyEventData(1)))
         .unwrap();
 
-    assert!(r == &States::State2(MyStateData(1)));
+    assert!(r == Handled::Transition(&States::State2(MyStateData(2))));
     assert_eq!(val, 2);
-}
-*/
 
-fn main() {
-    //TODO?
+    // The temporary context may be a different one on every call
+    let mut other = 10;
+    let r = sm.process_event(&mut other, Events::Event2).unwrap();
+
+    assert!(r == Handled::Transition(&States::State3));
+    assert_eq!(other, 12);
 }
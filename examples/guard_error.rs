@@ -0,0 +1,81 @@
+//! Guard error example
+//!
+//! An example of guards which tell why they refused a transition, by returning an error of the
+//! type declared with `guard_error`.
+
+#![deny(missing_docs)]
+
+use smlang::{statemachine, Handled};
+
+///Events
+pub enum Events {
+    ///Start the motor
+    Start,
+    ///Stop the motor
+    Stop,
+}
+
+/// Reasons for refusing to start the motor
+#[derive(Debug, PartialEq)]
+pub enum StartError {
+    /// The battery is too low to start
+    LowBattery,
+    /// The motor is too hot to start
+    Overheated,
+}
+
+statemachine! {
+    guard_error: StartError,
+    transitions: {
+        *Stopped + Start [ctx.can_start()] = Running,
+        Running + Stop [ctx.stoppable] = Stopped,
+    }
+}
+
+/// Context
+pub struct Context {
+    battery: u8,
+    temperature: u8,
+    stoppable: bool,
+}
+
+impl Context {
+    fn can_start(&mut self) -> Result<(), StartError> {
+        if self.battery < 10 {
+            Err(StartError::LowBattery)
+        } else if self.temperature > 80 {
+            Err(StartError::Overheated)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn main() {
+    let mut sm = StateMachine::new(Context {
+        battery: 5,
+        temperature: 20,
+        stoppable: false,
+    });
+
+    // Guards returning a `Result` report their error
+    let r = sm.process_event(Events::Start);
+    assert!(r == Err(Error::GuardError(StartError::LowBattery)));
+
+    sm.context_mut().battery = 100;
+    sm.context_mut().temperature = 90;
+    let r = sm.process_event(Events::Start);
+    assert!(r == Err(Error::GuardError(StartError::Overheated)));
+
+    sm.context_mut().temperature = 20;
+    let r = sm.process_event(Events::Start);
+    assert!(r == Ok(Handled::Transition(&States::Running)));
+
+    // Guards returning a `bool` keep working alongside them
+    let r = sm.process_event(Events::Stop);
+    assert!(r == Err(Error::GuardFailed));
+
+    sm.context_mut().stoppable = true;
+    let r = sm.process_event(Events::Stop);
+    assert!(r == Ok(Handled::Transition(&States::Stopped)));
+}
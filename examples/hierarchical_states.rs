@@ -0,0 +1,97 @@
+//! Hierarchical states example
+//!
+//! An example of composite states, where an event not handled by a sub-state is handled by the
+//! enclosing state instead.
+
+#![deny(missing_docs)]
+
+use smlang::statemachine;
+
+///Events
+pub enum Events {
+    ///Start working on a job
+    Start,
+    ///Pause the current job
+    Pause,
+    ///Finish the current job
+    Done,
+    ///Something went wrong
+    Fault,
+    ///Recover from a fault
+    Reset,
+}
+
+statemachine! {
+    states: {
+        Operational {
+            entry: ctx.log("enter Operational"),
+            exit: ctx.log("exit Operational"),
+            states: {
+                *Idle,
+                Busy {
+                    entry: ctx.log("enter Busy"),
+                    exit: ctx.log("exit Busy"),
+                },
+                Paused,
+            },
+        },
+    },
+    transitions: {
+        *Operational + Fault / ctx.log("fault"); = Error,
+        Idle + Start = Busy,
+        Busy + Pause = Paused,
+        Busy + Done = Idle,
+        Paused + Start = Busy,
+
+        // Paused handles faults itself, the other sub-states use the transition of Operational
+        Paused + Fault / ctx.log("fault while paused"); = Idle,
+
+        Error + Reset = Operational,
+    }
+}
+
+/// Context
+#[derive(Default)]
+pub struct Context {
+    log: Vec<&'static str>,
+}
+
+impl Context {
+    fn log(&mut self, msg: &'static str) {
+        self.log.push(msg);
+    }
+}
+
+fn main() {
+    let mut sm = StateMachine::new(Context::default());
+
+    // Composite states start in their initial sub-state
+    assert!(sm.state() == &States::Idle);
+    assert!(sm.state().is_in(CompositeStates::Operational));
+
+    sm.process_event(Events::Start).unwrap();
+    assert!(sm.state() == &States::Busy);
+    assert_eq!(sm.context().log, ["enter Busy"]);
+    sm.context_mut().log.clear();
+
+    // Busy does not handle Fault, so the transition of Operational is taken
+    sm.process_event(Events::Fault).unwrap();
+    assert!(sm.state() == &States::Error);
+    assert!(!sm.state().is_in(CompositeStates::Operational));
+    assert_eq!(sm.context().log, ["exit Busy", "exit Operational", "fault"]);
+    sm.context_mut().log.clear();
+
+    // Entering Operational enters its initial sub-state
+    sm.process_event(Events::Reset).unwrap();
+    assert!(sm.state() == &States::Idle);
+    assert_eq!(sm.context().log, ["enter Operational"]);
+    sm.context_mut().log.clear();
+
+    // Paused overrides the Fault transition of Operational
+    sm.process_event(Events::Start).unwrap();
+    sm.process_event(Events::Pause).unwrap();
+    sm.context_mut().log.clear();
+    sm.process_event(Events::Fault).unwrap();
+    assert!(sm.state() == &States::Idle);
+    assert_eq!(sm.context().log, ["fault while paused"]);
+}
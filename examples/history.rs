@@ -0,0 +1,128 @@
+//! History example
+//!
+//! An example of composite states which resume the sub-state they were in when they were left,
+//! instead of starting over from their initial sub-state.
+
+#![deny(missing_docs)]
+
+use smlang::statemachine;
+
+///Events
+pub enum Events {
+    ///Go to the next step of the program
+    Next,
+    ///Open the door in the middle of a program
+    Open,
+    ///Close the door and resume the program
+    Close,
+    ///Close the door and restart the program
+    Restart,
+    ///The power was cut
+    PowerLoss,
+    ///The power is back
+    PowerBack,
+    ///Pause the program, resuming it where it was
+    Pause,
+    ///The drum is jammed
+    Jam,
+    ///The drum is free again, resuming the program
+    Unjam,
+}
+
+statemachine! {
+    states: {
+        Program {
+            entry: ctx.log("enter Program"),
+            states: {
+                *Washing {
+                    states: {
+                        *Filling,
+                        Spinning {
+                            entry: ctx.log("enter Spinning"),
+                        },
+                    },
+                },
+                Rinsing(u32),
+            },
+        },
+    },
+    transitions: {
+        *Program + Open = DoorOpen,
+        Program + PowerLoss = Off,
+        Filling + Next = Spinning,
+        Spinning + Next = Rinsing(3),
+
+        // The data of the state being left can be used by the destination state
+        Rinsing + Jam = Jammed(*state),
+        Jammed(u32) + Unjam = Program(H),
+
+        // Shallow history resumes the sub-state of Program only, deep history the innermost one
+        DoorOpen + Close = Program(H),
+        DoorOpen + Restart = Program,
+        Off + PowerBack = Program(H*),
+
+        // A composite state can resume its own history
+        Program + Pause = Program(H*),
+    }
+}
+
+/// Context
+#[derive(Default)]
+pub struct Context {
+    log: Vec<&'static str>,
+}
+
+impl Context {
+    fn log(&mut self, msg: &'static str) {
+        self.log.push(msg);
+    }
+}
+
+fn main() {
+    let mut sm = StateMachine::new(Context::default());
+    assert!(sm.state() == &States::Filling);
+
+    sm.process_event(Events::Next).unwrap();
+    assert!(sm.state() == &States::Spinning);
+
+    // Shallow history resumes Washing, which starts over from Filling
+    sm.process_event(Events::Open).unwrap();
+    assert!(sm.state() == &States::DoorOpen);
+    sm.context_mut().log.clear();
+    sm.process_event(Events::Close).unwrap();
+    assert!(sm.state() == &States::Filling);
+    assert_eq!(sm.context().log, ["enter Program"]);
+    sm.context_mut().log.clear();
+
+    // Deep history resumes Spinning, running the entry actions of all the resumed states
+    sm.process_event(Events::Next).unwrap();
+    sm.process_event(Events::PowerLoss).unwrap();
+    assert!(sm.state() == &States::Off);
+    sm.context_mut().log.clear();
+    sm.process_event(Events::PowerBack).unwrap();
+    assert!(sm.state() == &States::Spinning);
+    assert_eq!(sm.context().log, ["enter Program", "enter Spinning"]);
+
+    // Leaf sub-states are resumed as they were
+    sm.process_event(Events::Next).unwrap();
+    sm.process_event(Events::Open).unwrap();
+    sm.process_event(Events::Close).unwrap();
+    assert!(sm.state() == &States::Rinsing(3));
+
+    // The state data is still there when resuming, after being used by the destination
+    sm.process_event(Events::Jam).unwrap();
+    assert!(sm.state() == &States::Jammed(3));
+    sm.process_event(Events::Unjam).unwrap();
+    assert!(sm.state() == &States::Rinsing(3));
+
+    // Resuming its own history leaves Program and enters it again where it was
+    sm.context_mut().log.clear();
+    sm.process_event(Events::Pause).unwrap();
+    assert!(sm.state() == &States::Rinsing(3));
+    assert_eq!(sm.context().log, ["enter Program"]);
+
+    // Without history, the initial sub-states are entered
+    sm.process_event(Events::Open).unwrap();
+    sm.process_event(Events::Restart).unwrap();
+    assert!(sm.state() == &States::Filling);
+}
@@ -0,0 +1,53 @@
+//! Named state machines example
+//!
+//! An example of two state machines in the same module, where the names of the generated items
+//! are prefixed with the name of each state machine.
+
+#![deny(missing_docs)]
+
+use smlang::{statemachine, Handled};
+
+statemachine! {
+    name: Motor,
+    events: { Start, Stop },
+    transitions: {
+        *Stopped + Start / ctx.starts += 1; = Running,
+        Running + Stop = Stopped,
+    }
+}
+
+statemachine! {
+    name: Door,
+    events: { Open, Close },
+    // Both state machines share the same context type
+    context_type: MotorContext,
+    transitions: {
+        *Closed + Open / ctx.openings += 1; = Opened,
+        Opened + Close = Closed,
+    }
+}
+
+/// Context
+#[derive(Default)]
+pub struct MotorContext {
+    starts: usize,
+    openings: usize,
+}
+
+fn main() {
+    let mut motor = MotorStateMachine::new(MotorContext::default());
+    let mut door = DoorStateMachine::new(MotorContext::default());
+
+    let r = motor.process_event(MotorEvents::Start);
+    assert!(r == Ok(Handled::Transition(&MotorStates::Running)));
+    let r = motor.process_event(MotorEvents::Start);
+    assert!(r == Err(MotorError::InvalidEvent));
+
+    let r = door.process_event(DoorEvents::Open);
+    assert!(r == Ok(Handled::Transition(&DoorStates::Opened)));
+    let r = door.process_event(DoorEvents::Open);
+    assert!(r == Err(DoorError::InvalidEvent));
+
+    assert_eq!(motor.context().starts, 1);
+    assert_eq!(door.context().openings, 1);
+}
@@ -0,0 +1,73 @@
+//! Orthogonal regions example
+//!
+//! An example of independent parts of a system, each with its own states, which share the same
+//! context and all receive every event.
+
+#![deny(missing_docs)]
+
+use smlang::{statemachine, Handled};
+
+///Events
+pub enum Events {
+    ///A link was established
+    LinkUp,
+    ///The link was lost
+    LinkDown,
+    ///The battery is running low
+    BatteryLow,
+    ///The device is going to sleep
+    Sleep,
+}
+
+statemachine! {
+    regions: {
+        Connectivity {
+            transitions: {
+                *Disconnected + LinkUp / ctx.connections += 1; = Connected,
+                Connected + LinkDown = Disconnected,
+                _ + Sleep = Disconnected,
+            },
+        },
+        Power {
+            transitions: {
+                *Normal + BatteryLow = Saving,
+                _ + Sleep = Off,
+            },
+        },
+    },
+}
+
+/// Context
+#[derive(Default)]
+pub struct Context {
+    connections: usize,
+}
+
+fn main() {
+    let mut sm = StateMachine::new(Context::default());
+    assert!(sm.state().connectivity == ConnectivityStates::Disconnected);
+    assert!(sm.state().power == PowerStates::Normal);
+
+    // Only the regions with a transition for the event report a new state
+    let r = sm.process_event(Events::LinkUp);
+    assert!(r.connectivity == Ok(Handled::Transition(&ConnectivityStates::Connected)));
+    assert!(r.power == Err(Error::InvalidEvent));
+
+    let r = sm.process_event(Events::BatteryLow);
+    assert!(r.connectivity == Err(Error::InvalidEvent));
+    assert!(r.power == Ok(Handled::Transition(&PowerStates::Saving)));
+
+    // Every region receives every event
+    let r = sm.process_event(Events::Sleep);
+    assert!(r.connectivity == Ok(Handled::Transition(&ConnectivityStates::Disconnected)));
+    assert!(r.power == Ok(Handled::Transition(&PowerStates::Off)));
+
+    assert!(
+        sm.state()
+            == &States {
+                connectivity: ConnectivityStates::Disconnected,
+                power: PowerStates::Off,
+            }
+    );
+    assert_eq!(sm.context().connections, 1);
+}
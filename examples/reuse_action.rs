@@ -4,7 +4,7 @@
 
 #![deny(missing_docs)]
 
-use smlang::statemachine;
+use smlang::{statemachine, Handled};
 
 ///Events
 pub enum Events {
@@ -40,15 +40,15 @@ fn main() {
 
     // triggers action
     let r = sm.process_event(Events::Event1);
-    assert!(r == Some(&States::State2));
+    assert!(r == Ok(Handled::Transition(&States::State2)));
     assert!(sm.context.0 == 1);
 
     let r = sm.process_event(Events::Event2);
-    assert!(r == Some(&States::State1));
+    assert!(r == Ok(Handled::Transition(&States::State1)));
     assert!(sm.context.0 == 1);
 
     // triggers the same action
     let r = sm.process_event(Events::Event2);
-    assert!(r == Some(&States::State3));
+    assert!(r == Ok(Handled::Transition(&States::State3)));
     assert!(sm.context.0 == 2);
 }
@@ -0,0 +1,35 @@
+//! SCXML example
+//!
+//! An example of exporting a state machine as a W3C SCXML document, for SCXML-based tools. Run
+//! with `--features scxml`.
+
+#![deny(missing_docs)]
+
+use smlang::statemachine;
+
+statemachine! {
+    name: Door,
+    events: { Open, Close, Lock, Unlock(u32) },
+    diagram: "target/diagrams/door.scxml",
+    transitions: {
+        *Closed + Open = Opened,
+        Opened + Close = Closed,
+        Closed + Lock = Locked,
+        Locked + Unlock(_) [*event == 1234] = Closed,
+    }
+}
+
+/// Context
+pub struct DoorContext;
+
+fn main() {
+    let scxml = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/target/diagrams/door.scxml"
+    ))
+    .unwrap();
+    assert_eq!(scxml, DOOR_STATEMACHINE_SCXML);
+    assert!(scxml.contains("<transition event=\"Lock\" target=\"Locked\"/>"));
+
+    print!("{}", DOOR_STATEMACHINE_SCXML);
+}
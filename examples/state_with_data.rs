@@ -4,7 +4,7 @@
 
 #![deny(missing_docs)]
 
-use smlang::statemachine;
+use smlang::{statemachine, Handled};
 
 /// State data
 #[derive(PartialEq, Debug, Clone)]
@@ -13,7 +13,7 @@ pub struct MyStateData(pub u32);
 // you must implement (or derive) Default to have an initial state with data
 impl Default for MyStateData {
     fn default() -> Self {
-        Self(20)
+        Self(42)
     }
 }
 
@@ -28,14 +28,14 @@ pub enum Events {
 }
 
 statemachine! {
+    states: {
+        // State3 has no transitions of its own, so its data is declared here
+        State3(usize),
+    },
     transitions: {
         *State1(MyStateData) + Event1 = State2(state.clone()),
         State2(MyStateData) + Event2 [state.0 == 42] = State1(MyStateData(2084)),
-        State2(MyStateData) + Event2 [state.0 == 2084] = State3(1),
-
-        //hack to get around not being able to have data with terminal state..
-        //add a transition that will never happen (guard is false)
-        State3(usize) + Event1 [false] = State3(3)
+        State2 + Event2 [state.0 == 2084] = State3(1),
         // ...
     }
 }
@@ -47,5 +47,16 @@ fn main() {
     let mut sm = StateMachine::new(Context);
     let result = sm.process_event(Events::Event1);
 
-    assert!(result == Some(&States::State2(MyStateData(42))));
+    assert!(result == Ok(Handled::Transition(&States::State2(MyStateData(42)))));
+
+    let result = sm.process_event(Events::Event2);
+    assert!(result == Ok(Handled::Transition(&States::State1(MyStateData(2084)))));
+
+    sm.process_event(Events::Event1).unwrap();
+    let result = sm.process_event(Events::Event2);
+    assert!(result == Ok(Handled::Transition(&States::State3(1))));
+
+    // Nothing leaves State3
+    assert!(sm.state().is_terminal());
+    assert!(sm.process_event(Events::Event1) == Err(Error::InvalidEvent));
 }
@@ -0,0 +1,40 @@
+//! State machine file example
+//!
+//! An example of generating a state machine from a file, with the same syntax as in the
+//! `statemachine!` macro.
+
+#![deny(missing_docs)]
+
+use smlang::statemachine_file;
+
+statemachine_file!("examples/machines/door.sm");
+
+/// Context
+#[derive(Default)]
+pub struct DoorContext {
+    code: u32,
+}
+
+impl DoorContext {
+    fn set_code(&mut self, code: u32) {
+        self.code = code;
+    }
+
+    fn is_code(&mut self, code: &u32) -> bool {
+        *code == self.code
+    }
+}
+
+fn main() {
+    let mut sm = DoorStateMachine::new(DoorContext::default());
+    assert!(*sm.state() == DoorStates::Closed);
+
+    sm.process_event(DoorEvents::Lock(1234)).unwrap();
+    assert!(*sm.state() == DoorStates::Locked);
+
+    assert!(sm.process_event(DoorEvents::Unlock(4321)) == Err(DoorError::GuardFailed));
+    assert!(*sm.state() == DoorStates::Locked);
+
+    sm.process_event(DoorEvents::Unlock(1234)).unwrap();
+    assert!(*sm.state() == DoorStates::Closed);
+}
@@ -10,6 +10,9 @@
 //! **documentation of the project**, this comes from the procedural macro also generating
 //! documentation.
 //!
+//! The documentation of the generated `StateMachine` lists all of its transitions in a table, with
+//! their states, events, guards and actions, so `cargo doc` shows the whole state machine.
+//!
 //! # DSL
 //!
 //! Please consult the README for the DSL specification.
@@ -20,9 +23,12 @@
 //!
 //! # Errors
 //!
-//! `StateMachine::process_event` will return `Ok(NextState)` if the transition was successful,
-//! or `Err(Error::GuardFailed)` if the guard failed, or `Err(Error::InvalidEvent)` if an event
-//! which should not come at this stage of the state machine was processed.
+//! `StateMachine::process_event` will return `Ok(Handled::Transition(&NextState))` if the
+//! transition was successful, or `Ok(Handled::Internal(&State))` if the event was handled without
+//! changing state, or `Err(Error::GuardFailed)` if the guard failed, or `Err(Error::InvalidEvent)`
+//! if an event which should not come at this stage of the state machine was processed.
+//! With a `guard_error` or an `action_error` declared, the errors of failing guards and actions
+//! are returned as `Err(Error::GuardError(err))` and `Err(Error::ActionError(err))`.
 //!
 //! # Panics
 //!
@@ -45,10 +51,17 @@
 //! ```
 //!
 //! ```ignore
+//! // Auto generated enum of errors from processing an event
+//! enum Error { ... }
+//! ```
+//!
+//! ```ignore
 //! // Auto generated struct which holds the state machine implementation
 //! struct StateMachine { ... }
 //! ```
 //!
+//! With a `name` given to the state machine, all of them are prefixed with it, e.g. `MotorStates`.
+//!
 //! # State machine generated API
 //!
 //! ```ignore
@@ -59,11 +72,79 @@
 //!     /// Returns the current state
 //!     pub fn state(&self) -> States;
 //!
-//!     /// Process an event
-//!     pub fn process_event(&mut self, event: Events) -> Result<States, Error>;
+//!     /// Process an event, with a first `temporary_context` argument if one is declared
+//!     pub fn process_event(&mut self, event: Events) -> Result<Handled<&States>, Error>;
 //! }
 //! ```
 
 #![no_std]
 
-pub use smlang_macros::statemachine;
+pub use smlang_macros::{statemachine, statemachine_file};
+
+/// How a state machine handled an event, returned by `process_event`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Handled<S> {
+    /// A transition was taken, to the state it holds.
+    Transition(S),
+    /// A transition without a destination state ran its action, leaving the state machine in the
+    /// state it holds.
+    Internal(S),
+}
+
+impl<S> Handled<S> {
+    /// The state the state machine is in after handling the event.
+    #[inline(always)]
+    pub fn into_state(self) -> S {
+        match self {
+            Handled::Transition(state) | Handled::Internal(state) => state,
+        }
+    }
+}
+
+/// The outcome of a guard, used by the generated code to accept guards returning either `bool`
+/// or `Result<(), E>` when a `guard_error` is declared.
+#[doc(hidden)]
+pub trait GuardResult<E> {
+    /// `Ok(())` if the guard passed, otherwise the error it failed with, if any.
+    fn into_guard_result(self) -> Result<(), Option<E>>;
+}
+
+impl<E> GuardResult<E> for bool {
+    #[inline(always)]
+    fn into_guard_result(self) -> Result<(), Option<E>> {
+        if self {
+            Ok(())
+        } else {
+            Err(None)
+        }
+    }
+}
+
+impl<E> GuardResult<E> for Result<(), E> {
+    #[inline(always)]
+    fn into_guard_result(self) -> Result<(), Option<E>> {
+        self.map_err(Some)
+    }
+}
+
+/// The outcome of an action, used by the generated code to accept actions returning either `()`
+/// or `Result<(), E>` when an `action_error` is declared.
+#[doc(hidden)]
+pub trait ActionResult<E> {
+    /// `Ok(())` if the action succeeded, otherwise the error it failed with.
+    fn into_action_result(self) -> Result<(), E>;
+}
+
+impl<E> ActionResult<E> for () {
+    #[inline(always)]
+    fn into_action_result(self) -> Result<(), E> {
+        Ok(())
+    }
+}
+
+impl<E> ActionResult<E> for Result<(), E> {
+    #[inline(always)]
+    fn into_action_result(self) -> Result<(), E> {
+        self
+    }
+}
@@ -0,0 +1,21 @@
+extern crate smlang;
+
+use smlang::statemachine;
+
+#[derive(PartialEq)]
+pub enum Events {
+    Event1,
+    Event2,
+}
+
+pub struct Context;
+
+statemachine! {
+    transitions: {
+        *State1 + Event1 = State2(1),
+        State2(u32) + Event1 = State1,
+        State2(i64) + Event2 = State1, //~ ERROR State State2 is declared with data (i64) here
+    }
+}
+
+fn main() {}
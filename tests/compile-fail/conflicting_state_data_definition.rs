@@ -0,0 +1,23 @@
+extern crate smlang;
+
+use smlang::statemachine;
+
+#[derive(PartialEq)]
+pub enum Events {
+    Event1,
+    Event2,
+}
+
+pub struct Context;
+
+statemachine! {
+    states: {
+        State2(i64), //~ ERROR State State2 is declared with data (i64) in the states block
+    },
+    transitions: {
+        *State1 + Event1 = State2(1),
+        State2(u32) + Event2 = State1,
+    }
+}
+
+fn main() {}
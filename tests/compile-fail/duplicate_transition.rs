@@ -0,0 +1,21 @@
+extern crate smlang;
+
+use smlang::statemachine;
+
+pub struct Context;
+
+pub enum Events {
+    Event1,
+    Event2,
+}
+
+statemachine! {
+    transitions: {
+        *State1 + Event1 = State2,
+        State2 + Event2 = State1,
+        State1 + Event1 = State3, //~ ERROR Transition State1 + Event1 is never taken
+        State3 + Event2 = State1,
+    }
+}
+
+fn main() {}
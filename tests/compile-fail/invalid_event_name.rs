@@ -0,0 +1,20 @@
+extern crate smlang;
+
+use smlang::statemachine;
+
+#[derive(PartialEq)]
+pub enum Events {
+    Event1,
+    Event2,
+}
+
+pub struct Context;
+
+statemachine! {
+    transitions: {
+        *State1 + 1 = State2, //~ ERROR Expected the name of an event after +
+        State2 + Event2 = State1,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+extern crate smlang;
+
+use smlang::statemachine;
+
+#[derive(PartialEq)]
+pub enum Events {
+    Event1,
+    Event2,
+}
+
+pub struct Context;
+
+statemachine! {
+    transitions: {
+        *State1 + Event1 = State2,
+        3 + Event2 = State1, //~ ERROR Expected the name of a state, or _ for a transition from any state
+    }
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+extern crate smlang;
+
+use smlang::statemachine;
+
+#[derive(PartialEq)]
+pub enum Events {
+    Event1,
+    Event2,
+}
+
+pub struct Context;
+
+statemachine! {
+    transitions: {
+        *State1 + Event1 = 2, //~ ERROR Expected the name of the state to go to after =
+        State2 + Event2 = State1,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+extern crate smlang;
+
+use smlang::statemachine;
+
+statemachine! {
+    regions: {
+        Main {
+            transitions: {
+                *Idle + Start = Running,
+                Running + Stop = Idle,
+            },
+        },
+        Loop { //~ ERROR Region Loop would be stored in the field `loop`, which is a keyword
+            transitions: {
+                *Off + Start = On,
+                On + Stop = Off,
+            },
+        },
+    },
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+extern crate smlang;
+
+use smlang::statemachine;
+
+#[derive(PartialEq)]
+pub enum Events {
+    Event1,
+    Event2,
+}
+
+pub struct Context;
+
+statemachine! {
+    states: {
+        State2 { //~ ERROR No initial sub-state defined for State2, indicate it with a *.
+            states: { State3, State4 },
+        },
+    },
+    transitions: {
+        *State1 + Event1 = State2,
+        State3 + Event2 = State4,
+    }
+}
+
+fn main() {}
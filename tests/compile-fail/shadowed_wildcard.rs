@@ -0,0 +1,21 @@
+extern crate smlang;
+
+use smlang::statemachine;
+
+pub struct Context;
+
+pub enum Events {
+    Event1,
+    Reset,
+}
+
+statemachine! {
+    transitions: {
+        *State1 + Event1 = State2,
+        State1 + Reset = State1,
+        State2 + Reset = State1,
+        _ + Reset = State1, //~ ERROR Transition _ + Reset is never taken
+    }
+}
+
+fn main() {}
@@ -0,0 +1,27 @@
+extern crate smlang;
+
+use smlang::statemachine;
+
+#[derive(PartialEq)]
+pub enum Events {
+    Event1,
+    Event2,
+}
+
+pub struct Context;
+
+statemachine! {
+    transitions: {
+        Idle + Event2 = Idle, //~ ERROR When regions are used, all transitions and states must be inside of them
+    },
+    regions: {
+        Main {
+            transitions: {
+                *State1 + Event1 = State2,
+                State2 + Event2 = State1,
+            },
+        },
+    },
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+extern crate smlang;
+
+use smlang::statemachine;
+
+pub struct Context;
+
+statemachine! {
+    events: { Event1, Event2 },
+    transitions: {
+        *State1 + Event1 = State2,
+        State2 + Evnt2 = State1, //~ ERROR Event Evnt2 is not declared in events, did you mean Event2?
+    }
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+extern crate smlang;
+
+use smlang::statemachine;
+
+#[derive(PartialEq)]
+pub enum Events {
+    Event1,
+    Event2,
+}
+
+pub struct Context;
+
+statemachine! {
+    diagram: "target/diagrams/machine.txt", //~ ERROR Unknown diagram file type
+    transitions: {
+        *State1 + Event1 = State2,
+        State2 + Event2 = State1,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+extern crate smlang;
+
+use smlang::statemachine;
+
+#[derive(PartialEq)]
+pub enum Events {
+    Event1,
+    Event2,
+}
+
+pub struct Context;
+
+statemachine! {
+    transitions: {
+        *State1 + Event1 = State2,
+        State2 + Event2 = State1,
+    },
+    transition_table: {} //~ ERROR Unknown keyword transition_table
+}
+
+fn main() {}
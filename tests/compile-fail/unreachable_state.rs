@@ -0,0 +1,20 @@
+extern crate smlang;
+
+use smlang::statemachine;
+
+pub struct Context;
+
+pub enum Events {
+    Event1,
+    Event2,
+}
+
+statemachine! {
+    transitions: {
+        *State1 + Event1 = State2,
+        State2 + Event2 = State1,
+        State3 + Event1 = State1, //~ ERROR State State3 is unreachable
+    }
+}
+
+fn main() {}
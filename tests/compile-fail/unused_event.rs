@@ -0,0 +1,17 @@
+#![deny(deprecated)]
+
+extern crate smlang;
+
+use smlang::statemachine;
+
+pub struct Context;
+
+statemachine! {
+    events: { Event1, Event2, Event3 }, //~ ERROR Event Event3 is never handled by a transition
+    transitions: {
+        *State1 + Event1 = State2,
+        State2 + Event2 = State1,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+extern crate smlang;
+
+use smlang::statemachine;
+
+#[derive(PartialEq)]
+pub enum Events {
+    Event1,
+    Event2,
+}
+
+pub struct Context;
+
+statemachine! {
+    transitions: {
+        *_ + Event1 = State2, //~ ERROR The starting state cannot be a wildcard
+        State2 + Event2 = State1,
+    }
+}
+
+fn main() {}